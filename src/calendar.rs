@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+
+use chrono::{naive, Datelike};
+
+use crate::schedule::DailyTarget;
+
+/// A legitimate reason not to work on a day.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DayOff {
+    Holiday,
+    Vacation,
+    Sick,
+}
+
+impl std::str::FromStr for DayOff {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DayOff, String> {
+        match s {
+            "holiday" => Ok(DayOff::Holiday),
+            "vacation" => Ok(DayOff::Vacation),
+            "sick" => Ok(DayOff::Sick),
+            _ => Err(format!("expected holiday, vacation or sick: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for DayOff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DayOff::Holiday => write!(f, "holiday"),
+            DayOff::Vacation => write!(f, "vacation"),
+            DayOff::Sick => write!(f, "sick"),
+        }
+    }
+}
+
+/// A day that recurs every year.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rule {
+    /// A fixed month and day.
+    Fixed { month: u32, day: u32 },
+    /// The nth weekday of a month, or the last one if `n` is `None`.
+    NthWeekday {
+        n: Option<u8>,
+        weekday: chrono::Weekday,
+        month: u32,
+    },
+    /// A number of days after Easter Sunday, or before it if negative.
+    Easter { offset: i64 },
+}
+
+impl Rule {
+    /// The day of the rule in `year`, if there is one.
+    ///
+    /// ```
+    /// use djot_log::calendar::Rule;
+    /// let in_2024 = |rule: Rule| rule.date_in(2024).unwrap().to_string();
+    /// assert_eq!(in_2024("12-25".parse().unwrap()), "2024-12-25");
+    /// assert_eq!(in_2024("3rd mon of 01".parse().unwrap()), "2024-01-15");
+    /// assert_eq!(in_2024("last mon of may".parse().unwrap()), "2024-05-27");
+    /// assert_eq!(in_2024("easter-2".parse().unwrap()), "2024-03-29");
+    /// assert_eq!(in_2024("easter+1".parse().unwrap()), "2024-04-01");
+    /// ```
+    pub fn date_in(&self, year: i32) -> Option<naive::NaiveDate> {
+        match *self {
+            Rule::Fixed { month, day } => naive::NaiveDate::from_ymd_opt(year, month, day),
+            Rule::NthWeekday {
+                n: Some(n),
+                weekday,
+                month,
+            } => naive::NaiveDate::from_weekday_of_month_opt(year, month, weekday, n),
+            Rule::NthWeekday {
+                n: None,
+                weekday,
+                month,
+            } => {
+                let first = naive::NaiveDate::from_ymd_opt(year, month, 1)?;
+                let last = first
+                    .checked_add_months(chrono::Months::new(1))?
+                    .pred_opt()?;
+                let days_back = (7 + last.weekday().num_days_from_monday()
+                    - weekday.num_days_from_monday())
+                    % 7;
+                last.checked_sub_days(chrono::Days::new(days_back.into()))
+            }
+            Rule::Easter { offset } => {
+                easter(year)?.checked_add_signed(chrono::TimeDelta::try_days(offset)?)
+            }
+        }
+    }
+}
+
+/// Easter Sunday in the Gregorian calendar, with the anonymous Gregorian algorithm.
+fn easter(year: i32) -> Option<naive::NaiveDate> {
+    let a = year % 19;
+    let (b, c) = (year / 100, year % 100);
+    let (d, e) = (b / 4, b % 4);
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    naive::NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// Parses `MM-DD`, `<nth> <weekday> of <month>` where nth is `1st` to `5th` or `last`, or
+/// `easter` followed by an optional offset in days such as `easter+1`.
+impl std::str::FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rule, String> {
+        if let Some(offset) = s.strip_prefix("easter") {
+            return Ok(Rule::Easter {
+                offset: match offset {
+                    "" => 0,
+                    _ => offset
+                        .strip_prefix('+')
+                        .unwrap_or(offset)
+                        .parse()
+                        .map_err(|_| format!("expected a number of days: {}", offset))?,
+                },
+            });
+        }
+        let parts = s.split_whitespace().collect::<Vec<_>>();
+        match parts[..] {
+            [month_day] => {
+                let date =
+                    naive::NaiveDate::parse_from_str(&format!("2000-{}", month_day), "%Y-%m-%d")
+                        .map_err(|_| {
+                            format!("expected a month and day such as 12-25: {}", month_day)
+                        })?;
+                Ok(Rule::Fixed {
+                    month: date.month(),
+                    day: date.day(),
+                })
+            }
+            [n, weekday, "of", month] => Ok(Rule::NthWeekday {
+                n: match n {
+                    "1st" => Some(1),
+                    "2nd" => Some(2),
+                    "3rd" => Some(3),
+                    "4th" => Some(4),
+                    "5th" => Some(5),
+                    "last" => None,
+                    _ => return Err(format!("expected 1st to 5th or last: {}", n)),
+                },
+                weekday: weekday
+                    .parse()
+                    .map_err(|_| format!("expected a day of the week: {}", weekday))?,
+                month: month
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|m| (1..=12).contains(m))
+                    .or_else(|| {
+                        month
+                            .parse::<chrono::Month>()
+                            .ok()
+                            .map(|m| m.number_from_month())
+                    })
+                    .ok_or_else(|| format!("expected a month: {}", month))?,
+            }),
+            _ => Err(format!("expected a day of the year: {}", s)),
+        }
+    }
+}
+
+/// Days off, with an optional description.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Calendar {
+    days: BTreeMap<naive::NaiveDate, (DayOff, String)>,
+    rules: Vec<(Rule, DayOff, String)>,
+}
+
+impl Calendar {
+    /// Days listed explicitly take precedence over the days given by rules of the same calendar,
+    /// see [`Calendar::extend`] for those of other calendars.
+    pub fn day_off(&self, date: naive::NaiveDate) -> Option<(DayOff, &str)> {
+        self.days
+            .get(&date)
+            .map(|(day_off, description)| (day_off, description))
+            .or_else(|| {
+                self.rules
+                    .iter()
+                    .rev()
+                    .find(|(rule, _, _)| rule.date_in(date.year()) == Some(date))
+                    .map(|(_, day_off, description)| (day_off, description))
+            })
+            .map(|(day_off, description)| (*day_off, description.as_str()))
+    }
+
+    /// Adds the days off of `other`, whose days and rules take precedence over those of `self`,
+    /// even over the days that `self` lists explicitly.
+    ///
+    /// ```
+    /// use djot_log::calendar::{Calendar, DayOff};
+    /// let mut calendar: Calendar = "every 12-24 vacation Christmas Eve".parse().unwrap();
+    /// calendar.extend("every 12-24 holiday Christmas Eve".parse().unwrap());
+    /// let date = chrono::NaiveDate::from_ymd_opt(2024, 12, 24).unwrap();
+    /// assert_eq!(
+    ///     calendar.day_off(date),
+    ///     Some((DayOff::Holiday, "Christmas Eve"))
+    /// );
+    /// let mut calendar: Calendar = "2024-12-24 vacation".parse().unwrap();
+    /// calendar.extend("every 12-24 holiday Christmas Eve".parse().unwrap());
+    /// assert_eq!(
+    ///     calendar.day_off(date),
+    ///     Some((DayOff::Holiday, "Christmas Eve"))
+    /// );
+    /// ```
+    pub fn extend(&mut self, other: Calendar) {
+        // Explicit days come before rules in `day_off`, so drop those that a later rule gives.
+        self.days.retain(|date, _| {
+            !other
+                .rules
+                .iter()
+                .any(|(rule, _, _)| rule.date_in(date.year()) == Some(*date))
+        });
+        self.days.extend(other.days);
+        self.rules.extend(other.rules);
+    }
+
+    /// A target that is zero on the days off of this calendar, and `target` otherwise.
+    pub fn apply<T: DailyTarget>(&self, target: T) -> WithDaysOff<'_, T> {
+        WithDaysOff {
+            calendar: self,
+            target,
+        }
+    }
+}
+
+/// Parses one day off per line, as a day or an inclusive range of days, the kind of day off and
+/// an optional description. Empty lines and lines starting with `#` are ignored.
+///
+/// Lines starting with `every` give a [`Rule`] for a day off that recurs every year instead, such
+/// as `every 3rd mon of 01 holiday Martin Luther King Jr. Day`.
+///
+/// ```
+/// use djot_log::calendar::{Calendar, DayOff};
+/// use djot_log::schedule::{DailyTarget, Schedule};
+/// let calendar: Calendar = "
+/// ## Office calendar
+/// 2023-12-25 holiday Christmas
+/// 2023-12-27..2023-12-29 vacation
+/// "
+/// .parse()
+/// .unwrap();
+/// let date = |d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+/// assert_eq!(
+///     calendar.day_off(date("2023-12-25")),
+///     Some((DayOff::Holiday, "Christmas"))
+/// );
+/// assert_eq!(calendar.day_off(date("2023-12-26")), None);
+/// let rules: Calendar = "every easter+1 holiday Easter Monday".parse().unwrap();
+/// assert_eq!(
+///     rules.day_off(date("2031-04-14")),
+///     Some((DayOff::Holiday, "Easter Monday"))
+/// );
+/// assert_eq!(
+///     calendar.day_off(date("2023-12-28")),
+///     Some((DayOff::Vacation, ""))
+/// );
+/// let target = calendar.apply(Schedule::flat(chrono::TimeDelta::try_hours(8).unwrap()));
+/// assert_eq!(target.target(date("2023-12-26")).num_hours(), 8);
+/// assert_eq!(target.target(date("2023-12-29")).num_hours(), 0);
+/// assert_eq!(
+///     "2023-12-25 holiday\n2023-12-26 party\n".parse::<Calendar>(),
+///     Err("line 2: expected holiday, vacation or sick: party".to_string())
+/// );
+/// ```
+impl std::str::FromStr for Calendar {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Calendar, String> {
+        let mut calendar = Calendar::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            parse_line(&mut calendar, line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+        Ok(calendar)
+    }
+}
+
+fn parse_line(calendar: &mut Calendar, line: &str) -> Result<(), String> {
+    if let Some(rule) = line.strip_prefix("every ") {
+        return parse_rule(calendar, rule);
+    }
+    let mut parts = line.splitn(3, char::is_whitespace);
+    let days = parts.next().unwrap_or_default();
+    let day_off = parts
+        .next()
+        .ok_or_else(|| format!("expected a kind of day off after {}", days))?
+        .parse::<DayOff>()?;
+    let description = parts.next().unwrap_or_default().trim().to_string();
+    let parse_date = |d: &str| {
+        naive::NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| format!("expected a date such as 2024-01-01: {}", d))
+    };
+    let (first, last) = days.split_once("..").unwrap_or((days, days));
+    let (first, last) = (parse_date(first)?, parse_date(last)?);
+    if first > last {
+        return Err(format!("days out of order: {}", days));
+    }
+    for date in first.iter_days().take_while(|d| *d <= last) {
+        calendar.days.insert(date, (day_off, description.clone()));
+    }
+    Ok(())
+}
+
+fn parse_rule(calendar: &mut Calendar, line: &str) -> Result<(), String> {
+    let parts = line.split_whitespace().collect::<Vec<_>>();
+    let i = parts
+        .iter()
+        .position(|p| p.parse::<DayOff>().is_ok())
+        .ok_or_else(|| format!("expected holiday, vacation or sick: {}", line))?;
+    calendar.rules.push((
+        parts[..i].join(" ").parse()?,
+        parts[i].parse()?,
+        parts[i + 1..].join(" "),
+    ));
+    Ok(())
+}
+
+/// See [`Calendar::apply`].
+pub struct WithDaysOff<'a, T> {
+    calendar: &'a Calendar,
+    target: T,
+}
+
+impl<T: DailyTarget> DailyTarget for WithDaysOff<'_, T> {
+    fn target(&self, date: naive::NaiveDate) -> chrono::TimeDelta {
+        match self.calendar.day_off(date) {
+            Some(_) => chrono::TimeDelta::zero(),
+            None => self.target.target(date),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::naive;
+
+use crate::md::{Located, LogNode};
+
+/// The headings to append to a log to start an entry at `now` with the given kind paths, ending
+/// the running entry if there is one.
+///
+/// A day header is added when the day the log is at, after any midnight crossings, is not the day
+/// of `now`. As in [`crate::logs_from_nodes`], a time header before the start of the running entry
+/// ends it on the next day, so a running entry is an error only if `now` is neither on the day it
+/// started nor before its start time on the day after.
+///
+/// ```
+/// use djot_log::LogSource;
+/// let source = "# 2023-12-04\n\n## 09:00\n\n### Work\n";
+/// let nodes = djot_log::md::Markdown.log_nodes(source);
+/// let now = chrono::NaiveDateTime::parse_from_str("2023-12-04 13:00", "%Y-%m-%d %H:%M").unwrap();
+/// let kinds = vec![vec!["Work".to_string(), "MyOrg".to_string()], vec!["Coding".to_string()]];
+/// let appended = djot_log::clock::start(source, &nodes, now, &kinds).unwrap();
+/// assert_eq!(appended, "\n## 13:00\n\n### Coding\n\n### Work / MyOrg\n");
+///
+/// let tomorrow = now + chrono::TimeDelta::try_days(1).unwrap();
+/// assert!(djot_log::clock::start(source, &nodes, tomorrow, &kinds).is_err());
+/// let stopped = source.to_string() + "\n## 17:00\n";
+/// let nodes = djot_log::md::Markdown.log_nodes(&stopped);
+/// let appended = djot_log::clock::start(&stopped, &nodes, tomorrow, &kinds).unwrap();
+/// assert!(appended.starts_with("\n# 2023-12-05\n\n## 13:00\n"));
+///
+/// let crossed = "# 2023-12-04\n\n## 22:00\n\n### Work\n\n## 01:30\n\n### Other\n";
+/// let nodes = djot_log::md::Markdown.log_nodes(crossed);
+/// let night = tomorrow - chrono::TimeDelta::try_hours(10).unwrap();
+/// let appended = djot_log::clock::start(crossed, &nodes, night, &kinds);
+/// assert_eq!(appended.unwrap(), "\n## 03:00\n\n### Coding\n\n### Work / MyOrg\n");
+/// ```
+pub fn start(
+    source: &str,
+    nodes: &[Located<LogNode>],
+    now: naive::NaiveDateTime,
+    kinds: &[Vec<String>],
+) -> Result<String, String> {
+    // Ending a running entry moves the log to the day of `now`.
+    let day = match running_before(nodes, now)? {
+        true => Some(now.date()),
+        false => current_day(nodes),
+    };
+    let mut headings = vec![];
+    let time = now.format("%H:%M").to_string();
+    if day != Some(now.date()) {
+        headings.push(format!("# {}", now.date()));
+    }
+    // A time header that ends the last entry at the same time also starts the new one.
+    let ends_now = matches!(
+        nodes.last().map(|n| &n.value),
+        Some(LogNode::TimeHeader(header)) if header.time.format("%H:%M").to_string() == time
+    );
+    if day != Some(now.date()) || !ends_now {
+        headings.push(format!("## {}", time));
+    }
+    let mut kinds = kinds.to_vec();
+    kinds.sort();
+    kinds.dedup();
+    headings.extend(kinds.iter().map(|path| format!("### {}", path.join(" / "))));
+    Ok(append(source, &headings))
+}
+
+/// The heading to append to a log to end its running entry at `now`.
+///
+/// It is an error if there is no running entry, or if it cannot end at `now`, as for
+/// [`start`].
+///
+/// ```
+/// use djot_log::LogSource;
+/// let source = "# 2023-12-04\n\n## 09:00\n\n### Work\n";
+/// let now = chrono::NaiveDateTime::parse_from_str("2023-12-04 13:00", "%Y-%m-%d %H:%M").unwrap();
+/// let nodes = djot_log::md::Markdown.log_nodes(source);
+/// assert_eq!(djot_log::clock::stop(source, &nodes, now).unwrap(), "\n## 13:00\n");
+/// let tomorrow = now + chrono::TimeDelta::try_days(1).unwrap();
+/// assert_eq!(
+///     djot_log::clock::stop(source, &nodes, tomorrow),
+///     Err("the running entry started on 2023-12-04, end it by hand".to_string())
+/// );
+/// let stopped = source.to_string() + "\n## 13:00\n";
+/// assert!(djot_log::clock::stop(&stopped, &djot_log::md::Markdown.log_nodes(&stopped), now).is_err());
+///
+/// let late = "# 2023-12-04\n\n## 23:00\n\n### Work\n";
+/// let after_midnight = now + chrono::TimeDelta::try_minutes(690).unwrap();
+/// let nodes = djot_log::md::Markdown.log_nodes(late);
+/// assert_eq!(djot_log::clock::stop(late, &nodes, after_midnight).unwrap(), "\n## 00:30\n");
+/// ```
+pub fn stop(
+    source: &str,
+    nodes: &[Located<LogNode>],
+    now: naive::NaiveDateTime,
+) -> Result<String, String> {
+    match running_before(nodes, now)? {
+        true => Ok(append(source, &[format!("## {}", now.format("%H:%M"))])),
+        false => Err("no running entry to stop".to_string()),
+    }
+}
+
+/// Whether the log has a running entry, which is an error if a time header at `now` would not end
+/// it on the day of `now`.
+fn running_before(nodes: &[Located<LogNode>], now: naive::NaiveDateTime) -> Result<bool, String> {
+    match crate::logs_from_nodes(nodes.iter().cloned()).0.last() {
+        Some(log) if log.is_running() => {
+            let mut end = naive::NaiveDateTime::new(log.start.date(), now.time());
+            if end < log.start {
+                end += chrono::TimeDelta::try_days(1).unwrap();
+            }
+            match end.date() == now.date() {
+                true => Ok(true),
+                false => Err(format!(
+                    "the running entry started on {}, end it by hand",
+                    log.start.date()
+                )),
+            }
+        }
+        _ => Ok(false),
+    }
+}
+
+/// The day the log is at after its last node, as in [`crate::logs_from_nodes`], where a time
+/// header before the previous one is on the next day.
+fn current_day(nodes: &[Located<LogNode>]) -> Option<naive::NaiveDate> {
+    let mut day = None;
+    let mut previous = None;
+    for node in nodes {
+        match &node.value {
+            LogNode::DayHeader(header) => {
+                day = Some(header.date);
+                previous = None;
+            }
+            LogNode::TimeHeader(header) => {
+                if previous.is_some_and(|p| header.time < p) {
+                    day = day.and_then(|d: naive::NaiveDate| d.succ_opt());
+                }
+                previous = Some(header.time);
+            }
+            LogNode::KindHeader(_) | LogNode::Note(_) => {}
+        }
+    }
+    day
+}
+
+/// Separates the headings from each other and from the end of `source` with a blank line.
+fn append(source: &str, headings: &[String]) -> String {
+    let separator = match source {
+        "" => "",
+        s if s.ends_with("\n\n") => "",
+        s if s.ends_with('\n') => "\n",
+        _ => "\n\n",
+    };
+    format!(
+        "{}{}",
+        separator,
+        headings
+            .iter()
+            .map(|h| format!("{}\n", h))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
@@ -0,0 +1,172 @@
+use jotdown::{Container, Event};
+
+use crate::md::{Inline, Located, LogNode, Note, Position};
+
+/// The Djot log format.
+///
+/// ```
+/// use djot_log::LogSource;
+/// let source = std::fs::read_to_string("example.dj").unwrap();
+/// let markdown_source = std::fs::read_to_string("example.md").unwrap();
+/// assert_eq!(
+///     djot_log::djot::Djot.parse_log(&source),
+///     djot_log::parse_log(&markdown_source)
+/// );
+/// ```
+pub struct Djot;
+
+impl crate::LogSource for Djot {
+    fn log_nodes(&self, s: &str) -> Vec<Located<LogNode>> {
+        parse_log_nodes(s).collect()
+    }
+}
+
+/// Djot joins consecutive heading lines that start with the same `#`s into a single heading, so
+/// kind headers must be separated by blank lines.
+///
+/// ```
+/// let source = std::fs::read_to_string("example.dj").unwrap();
+/// let markdown_source = std::fs::read_to_string("example.md").unwrap();
+/// assert_eq!(
+///     format!(
+///         "{:?}",
+///         djot_log::djot::parse_log_nodes(&source)
+///             .map(|n| n.value)
+///             .collect::<Vec<_>>()
+///     ),
+///     format!(
+///         "{:?}",
+///         djot_log::md::parse_log_nodes(&djot_log::md::parse_markdown(&markdown_source))
+///             .map(|n| n.value)
+///             .collect::<Vec<_>>()
+///     )
+/// );
+/// ```
+pub fn parse_log_nodes(s: &str) -> impl Iterator<Item = Located<LogNode>> + '_ {
+    let mut events = jotdown::Parser::new(s)
+        .into_offset_iter()
+        .map(|(e, r)| (e, r.start));
+    let mut nodes = vec![];
+    while let Some((event, offset)) = events.next() {
+        let value = match event {
+            Event::Start(Container::Document | Container::Section { .. }, _) => continue,
+            Event::Start(Container::Heading { level, .. }, _) => LogNode::from_heading(
+                u8::try_from(level).unwrap_or(u8::MAX),
+                &plain_text(&inlines(&mut events)).replace('\n', " "),
+            ),
+            Event::Start(container @ (Container::Paragraph | Container::List { .. }), _) => {
+                Some(LogNode::Note(note(container, &mut events)))
+            }
+            Event::Start(_, _) => {
+                skip(&mut events);
+                None
+            }
+            _ => None,
+        };
+        if let Some(value) = value {
+            nodes.push(Located {
+                value,
+                position: Position::from_offset(s, offset),
+            });
+        }
+    }
+    nodes.into_iter()
+}
+
+/// Consumes events up to the end of the current container.
+fn skip<'s>(events: &mut impl Iterator<Item = (Event<'s>, usize)>) {
+    let mut depth = 0;
+    for (event, _) in events {
+        match event {
+            Event::Start(_, _) => depth += 1,
+            Event::End(_) if depth == 0 => return,
+            Event::End(_) => depth -= 1,
+            _ => {}
+        }
+    }
+}
+
+/// Parses a paragraph or list whose start event has just been consumed.
+fn note<'s>(
+    container: Container<'s>,
+    events: &mut impl Iterator<Item = (Event<'s>, usize)>,
+) -> Note {
+    match container {
+        Container::List { .. } => {
+            let mut items = vec![];
+            while let Some((event, _)) = events.next() {
+                match event {
+                    Event::Start(Container::ListItem | Container::TaskListItem { .. }, _) => {
+                        items.push(notes(events))
+                    }
+                    Event::Start(_, _) => skip(events),
+                    Event::End(_) => break,
+                    _ => {}
+                }
+            }
+            Note::List(items)
+        }
+        _ => Note::Paragraph(inlines(events)),
+    }
+}
+
+/// Parses the paragraphs and lists up to the end of the current container.
+fn notes<'s>(events: &mut impl Iterator<Item = (Event<'s>, usize)>) -> Vec<Note> {
+    let mut notes = vec![];
+    while let Some((event, _)) = events.next() {
+        match event {
+            Event::Start(container @ (Container::Paragraph | Container::List { .. }), _) => {
+                notes.push(note(container, events))
+            }
+            Event::Start(_, _) => skip(events),
+            Event::End(_) => break,
+            _ => {}
+        }
+    }
+    notes
+}
+
+/// Parses the text up to the end of the current container, keeping the characters that Djot
+/// replaces with typographic ones as they were written.
+fn inlines<'s>(events: &mut impl Iterator<Item = (Event<'s>, usize)>) -> Vec<Inline> {
+    let mut inlines = vec![];
+    let mut depth = 0;
+    while let Some((event, _)) = events.next() {
+        match event {
+            Event::Start(Container::Link(url, _), _) => {
+                let text = plain_text(&self::inlines(events));
+                inlines.push(Inline::Link {
+                    text,
+                    url: url.to_string(),
+                });
+            }
+            Event::Start(_, _) => depth += 1,
+            Event::End(_) if depth == 0 => break,
+            Event::End(_) => depth -= 1,
+            Event::Str(text) => Inline::push_text(&mut inlines, &text),
+            Event::Symbol(symbol) => Inline::push_text(&mut inlines, &format!(":{}:", symbol)),
+            Event::Softbreak | Event::Hardbreak => Inline::push_text(&mut inlines, "\n"),
+            Event::LeftSingleQuote | Event::RightSingleQuote => {
+                Inline::push_text(&mut inlines, "'")
+            }
+            Event::LeftDoubleQuote | Event::RightDoubleQuote => {
+                Inline::push_text(&mut inlines, "\"")
+            }
+            Event::Ellipsis => Inline::push_text(&mut inlines, "..."),
+            Event::EnDash => Inline::push_text(&mut inlines, "--"),
+            Event::EmDash => Inline::push_text(&mut inlines, "---"),
+            Event::NonBreakingSpace => Inline::push_text(&mut inlines, " "),
+            _ => {}
+        }
+    }
+    inlines
+}
+
+fn plain_text(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|i| match i {
+            Inline::Text(text) | Inline::Link { text, .. } => text.as_str(),
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+use chrono::naive;
+
+use crate::Log;
+
+/// Writes one row per log, with the kinds joined as in the `Display` of [`crate::Kinds`]. The end
+/// has its own date, which is after the date of the start for logs that cross midnight.
+///
+/// Running logs have an empty end; end them with [`Log::until`] to export their duration so far.
+///
+/// ```
+/// let source = std::fs::read_to_string("example.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// let mut csv = vec![];
+/// djot_log::export::write_logs_csv(logs.iter().take(2), &mut csv).unwrap();
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     "date,start,end_date,end,minutes,kinds
+/// 2023-12-03,09:00,2023-12-03,13:00,240,Coding // Work / MyOrg / MyDept / MyProj
+/// 2023-12-03,14:00,2023-12-03,15:00,60,Meeting // Work / MyOrg / MyDept
+/// "
+/// );
+///
+/// let (logs, _) = djot_log::parse_log("# 2023-12-03\n## 22:00\n### Work\n## 01:30\n### Work\n");
+/// let mut csv = vec![];
+/// djot_log::export::write_logs_csv(logs.iter(), &mut csv).unwrap();
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     "date,start,end_date,end,minutes,kinds
+/// 2023-12-03,22:00,2023-12-04,01:30,210,Work
+/// 2023-12-04,01:30,,,0,Work
+/// "
+/// );
+/// ```
+pub fn write_logs_csv<'a>(
+    logs: impl Iterator<Item = &'a Log>,
+    writer: impl std::io::Write,
+) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(["date", "start", "end_date", "end", "minutes", "kinds"])?;
+    for log in logs {
+        writer.write_record([
+            log.start.date().to_string(),
+            log.start.format("%H:%M").to_string(),
+            log.end().map(|e| e.date().to_string()).unwrap_or_default(),
+            log.end()
+                .map(|e| e.format("%H:%M").to_string())
+                .unwrap_or_default(),
+            log.duration().num_minutes().to_string(),
+            log.kinds().to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes one row per day, as returned by [`crate::total_by_day`].
+///
+/// ```
+/// let source = std::fs::read_to_string("example.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// let totals = djot_log::total_by_day(logs.iter(), djot_log::DayAttribution::StartDay);
+/// let mut csv = vec![];
+/// djot_log::export::write_totals_csv(&totals, &mut csv).unwrap();
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     "date,minutes\n2023-12-03,480\n2023-12-04,480\n"
+/// );
+/// ```
+pub fn write_totals_csv(
+    totals: &[(naive::NaiveDate, chrono::TimeDelta)],
+    writer: impl std::io::Write,
+) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(["date", "minutes"])?;
+    for (date, total) in totals {
+        writer.write_record([date.to_string(), total.num_minutes().to_string()])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::md::{Located, LogNode, TimeHeader};
+
+/// Rewrites the mechanical mistakes in a log: day sections out of order are sorted, times such
+/// as `9:00` are written as `09:00`, repeated kind headers within an entry are removed, and the
+/// whitespace around the parts of kind paths is trimmed.
+///
+/// Everything else, such as notes, is kept as it is. Only headings written on a single line
+/// starting with `#` are rewritten, and only from the text as written, so escapes and
+/// formatting within them are kept too.
+///
+/// ```
+/// use djot_log::LogSource;
+/// let source = "\
+/// ## 2023-12-04
+///
+/// ### 9:00
+///
+/// #### Work /  MyOrg
+///
+/// #### Work / MyOrg
+///
+/// * _Notes_ are kept
+///
+/// ### 17:00
+///
+/// ## 2023-12-03
+///
+/// ### 09:00
+///
+/// #### Work
+///
+/// ### 17:00
+/// ";
+/// let fixed = djot_log::fix::fix(source, &djot_log::md::Markdown.log_nodes(source));
+/// assert_eq!(
+///     fixed,
+///     "\
+/// ## 2023-12-03
+///
+/// ### 09:00
+///
+/// #### Work
+///
+/// ### 17:00
+///
+/// ## 2023-12-04
+///
+/// ### 09:00
+///
+/// #### Work / MyOrg
+///
+/// * _Notes_ are kept
+///
+/// ### 17:00
+/// "
+/// );
+/// ```
+pub fn fix(source: &str, nodes: &[Located<LogNode>]) -> String {
+    let mut lines = source
+        .split_inclusive('\n')
+        .map(|l| Some(l.to_string()))
+        .collect::<Vec<_>>();
+    let mut days = vec![];
+    let mut kinds = HashSet::new();
+    for node in nodes {
+        let i = node.position.line - 1;
+        match &node.value {
+            LogNode::DayHeader(header) => {
+                kinds.clear();
+                days.push((header.date, i));
+            }
+            LogNode::TimeHeader(header) => {
+                kinds.clear();
+                rewrite_heading(&mut lines[i], |text| {
+                    TimeHeader::parse(text.trim()).map(|_| header.time.format("%H:%M").to_string())
+                });
+            }
+            LogNode::KindHeader(header) => {
+                let path = header.path.iter().map(|p| p.trim()).collect::<Vec<_>>();
+                if kinds.insert(path.clone()) {
+                    rewrite_heading(&mut lines[i], |text| {
+                        Some(text.split(" / ").map(str::trim).join(" / "))
+                    });
+                } else if is_heading(&lines[i]) {
+                    lines[i] = None;
+                    // Also drop the blank line that separated the heading from the next line.
+                    if i > 0 && is_blank(&lines[i - 1]) && lines.get(i + 1).is_some_and(is_blank) {
+                        lines[i + 1] = None;
+                    }
+                }
+            }
+            LogNode::Note(_) => {}
+        }
+    }
+
+    if days.is_sorted_by_key(|(date, _)| *date) {
+        return lines.into_iter().flatten().collect();
+    }
+    let ends_with_newline = source.ends_with('\n');
+    let first = days.first().map_or(lines.len(), |(_, i)| *i);
+    let mut fixed = lines[..first].iter().flatten().cloned().collect::<String>();
+    let mut sections = vec![];
+    let mut trailers = vec![];
+    for (n, (date, start)) in days.iter().enumerate() {
+        let end = days.get(n + 1).map_or(lines.len(), |(_, i)| *i);
+        let section = lines[*start..end].iter().flatten().collect::<Vec<_>>();
+        let body = section.len()
+            - section
+                .iter()
+                .rev()
+                .take_while(|l| l.trim().is_empty())
+                .count();
+        let mut text = section[..body].iter().copied().cloned().collect::<String>();
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        sections.push((*date, text));
+        trailers.push(section[body..].iter().copied().cloned().collect::<String>());
+    }
+    sections.sort_by_key(|(date, _)| *date);
+    for ((_, text), trailer) in sections.into_iter().zip(trailers) {
+        fixed.push_str(&text);
+        fixed.push_str(&trailer);
+    }
+    if !ends_with_newline && fixed.ends_with('\n') {
+        fixed.pop();
+    }
+    fixed
+}
+
+fn is_heading(line: &Option<String>) -> bool {
+    line.as_ref()
+        .is_some_and(|l| l.trim_start().starts_with('#'))
+}
+
+fn is_blank(line: &Option<String>) -> bool {
+    line.as_ref().is_some_and(|l| l.trim().is_empty())
+}
+
+/// Replaces the text of a heading line as written, keeping its level and line ending.
+fn rewrite_heading(line: &mut Option<String>, rewrite: impl FnOnce(&str) -> Option<String>) {
+    let Some(l) = line.as_mut().filter(|l| l.trim_start().starts_with('#')) else {
+        return;
+    };
+    let prefix = l.len() - l.trim_start_matches([' ', '#']).len();
+    let ending = l.len() - l.trim_end_matches(['\n', '\r']).len();
+    if let Some(text) = rewrite(&l[prefix..l.len() - ending]) {
+        *l = format!("{}{}{}", &l[..prefix], text, &l[l.len() - ending..]);
+    }
+}
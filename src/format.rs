@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use crate::md::{Located, LogNode};
+
+/// Formats a log canonically: headings are surrounded by exactly one blank line, the kind headers
+/// of an entry are sorted, and the parts of kind paths are separated by ` / `.
+///
+/// A `/` with whitespace on either side separates the parts of a kind path, so `Work/ MyOrg`
+/// becomes `Work / MyOrg`, while `CI/CD` is kept as one part. Formatting a formatted log leaves
+/// it unchanged. As with [`crate::fix::fix`], only headings written on a single line starting with
+/// `#` are rewritten.
+///
+/// ```
+/// use djot_log::LogSource;
+/// let source = "\
+/// ## 2023-12-04
+/// ###   09:00
+/// #### Work/ MyOrg
+/// * Notes
+///
+///
+/// #### Coding
+/// ### 17:00
+/// ";
+/// let formatted = djot_log::format::format(source, &djot_log::md::Markdown.log_nodes(source));
+/// assert_eq!(
+///     formatted,
+///     "\
+/// ## 2023-12-04
+///
+/// ### 09:00
+///
+/// #### Coding
+///
+/// #### Work / MyOrg
+///
+/// * Notes
+///
+/// ### 17:00
+/// "
+/// );
+/// let nodes = djot_log::md::Markdown.log_nodes(&formatted);
+/// assert_eq!(djot_log::format::format(&formatted, &nodes), formatted);
+/// ```
+pub fn format(source: &str, nodes: &[Located<LogNode>]) -> String {
+    let mut lines = source
+        .split_inclusive('\n')
+        .map(|l| l.trim_end_matches(['\n', '\r']).to_string())
+        .collect::<Vec<_>>();
+    let mut headings = HashMap::new();
+    for node in nodes {
+        let i = node.position.line - 1;
+        if !lines[i].trim_start().starts_with('#') {
+            continue;
+        }
+        let text = lines[i].trim_start_matches([' ', '#']).trim().to_string();
+        let heading = match &node.value {
+            LogNode::DayHeader(_) => Heading::Day,
+            LogNode::TimeHeader(_) => Heading::Time,
+            LogNode::KindHeader(_) => Heading::Kind(kind_path(&text)),
+            LogNode::Note(_) => continue,
+        };
+        lines[i] = match &heading {
+            Heading::Day => format!("# {}", text),
+            Heading::Time => format!("## {}", text),
+            Heading::Kind(path) => format!("### {}", path.join(" / ")),
+        };
+        headings.insert(i, heading);
+    }
+
+    // Sorts the kind headers of each entry, and moves them to where the first of them was, after
+    // any notes before it.
+    let mut ordered: Vec<usize> = vec![];
+    let mut entry: Vec<usize> = vec![];
+    let mut kinds: Vec<usize> = vec![];
+    for i in 0..=lines.len() {
+        if i == lines.len() || matches!(headings.get(&i), Some(Heading::Day | Heading::Time)) {
+            kinds.sort_by_key(|k| match &headings[k] {
+                Heading::Kind(path) => path.clone(),
+                _ => unreachable!(),
+            });
+            let first = entry.iter().position(|j| kinds.contains(j));
+            let rest = entry.iter().copied().filter(|j| !kinds.contains(j));
+            match first {
+                Some(first) => ordered.extend(
+                    entry[..first]
+                        .iter()
+                        .chain(kinds.iter())
+                        .copied()
+                        .chain(rest.skip(first)),
+                ),
+                None => ordered.extend(rest),
+            }
+            entry.clear();
+            kinds.clear();
+        } else if let Some(Heading::Kind(_)) = headings.get(&i) {
+            kinds.push(i);
+        }
+        if i < lines.len() {
+            entry.push(i);
+        }
+    }
+
+    let mut formatted = String::new();
+    let mut blanks = 0;
+    let mut previous_heading = None;
+    for i in ordered {
+        if lines[i].trim().is_empty() {
+            blanks += 1;
+            continue;
+        }
+        let heading = headings.contains_key(&i);
+        if let Some(previous_heading) = previous_heading {
+            let blanks = if heading || previous_heading {
+                1
+            } else {
+                blanks
+            };
+            formatted.push_str(&"\n".repeat(blanks));
+        }
+        formatted.push_str(&lines[i]);
+        formatted.push('\n');
+        blanks = 0;
+        previous_heading = Some(heading);
+    }
+    formatted
+}
+
+enum Heading {
+    Day,
+    Time,
+    Kind(Vec<String>),
+}
+
+/// Splits a kind path at each `/` with whitespace on either side.
+fn kind_path(text: &str) -> Vec<String> {
+    let mut path = vec![];
+    let mut part = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '/'
+            && (part.ends_with(char::is_whitespace)
+                || chars.peek().is_some_and(|c| c.is_whitespace()))
+        {
+            path.push(part.trim().to_string());
+            part.clear();
+        } else {
+            part.push(c);
+        }
+    }
+    path.push(part.trim().to_string());
+    path
+}
@@ -1,58 +1,368 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use chrono::naive;
 use frozenset::Freeze;
 use itertools::Itertools;
 
+pub mod calendar;
+pub mod clock;
+pub mod djot;
+pub mod export;
+pub mod fix;
+pub mod format;
+pub mod lint;
 pub mod md;
+pub mod report;
+pub mod schedule;
+pub mod summary;
 
 #[derive(PartialEq, Eq, Hash, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Log {
     pub start: naive::NaiveDateTime,
-    end: naive::NaiveDateTime,
+    /// `None` for the in-progress entry at the end of a log.
+    end: Option<naive::NaiveDateTime>,
     kinds: Kinds,
+    notes: Vec<md::Note>,
 }
 
 impl Log {
-    fn duration(&self) -> chrono::TimeDelta {
-        self.end - self.start
+    pub fn new(
+        start: naive::NaiveDateTime,
+        end: Option<naive::NaiveDateTime>,
+        kinds: Kinds,
+        notes: Vec<md::Note>,
+    ) -> Log {
+        Log {
+            start,
+            end,
+            kinds,
+            notes,
+        }
+    }
+
+    /// `None` for the in-progress entry at the end of a log.
+    pub fn end(&self) -> Option<naive::NaiveDateTime> {
+        self.end
+    }
+
+    pub fn kinds(&self) -> &Kinds {
+        &self.kinds
+    }
+
+    /// The paragraphs and lists written under the kind headers of the entry.
+    ///
+    /// ```
+    /// let source = std::fs::read_to_string("example.md").unwrap();
+    /// let (logs, _) = djot_log::parse_log(&source);
+    /// assert_eq!(
+    ///     logs[1].notes().iter().map(|n| n.to_string()).collect::<Vec<_>>(),
+    ///     vec!["* Interesting!"]
+    /// );
+    /// ```
+    pub fn notes(&self) -> &[md::Note] {
+        &self.notes
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Ends an in-progress log at `now` if it started on the day of `now`; other logs are returned
+    /// unchanged.
+    ///
+    /// Running logs have no duration until they are ended, so an entry left open on an earlier
+    /// day counts for nothing, see [`unterminated_running_entry`].
+    ///
+    /// ```
+    /// let (logs, errors) = djot_log::parse_log("# 2023-12-03\n## 09:00\n### Work\n");
+    /// assert!(errors.is_empty());
+    /// assert!(logs[0].is_running());
+    /// assert_eq!(format!("{}", logs[0]), "2023-12-03 09:00:00- Work (running)");
+    /// let now = chrono::NaiveDate::from_ymd_opt(2023, 12, 3)
+    ///     .unwrap()
+    ///     .and_hms_opt(11, 30, 0)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     format!("{}", logs[0].until(now)),
+    ///     "2023-12-03 09:00:00-11:30:00 Work"
+    /// );
+    /// let next_day = now + chrono::TimeDelta::try_days(1).unwrap();
+    /// assert!(logs[0].until(next_day).is_running());
+    /// assert!(logs[0].until(next_day).duration().is_zero());
+    /// ```
+    pub fn until(&self, now: naive::NaiveDateTime) -> Log {
+        match self.end {
+            None if self.start.date() == now.date() => Log {
+                end: Some(now.max(self.start)),
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+
+    fn end_or_start(&self) -> naive::NaiveDateTime {
+        self.end.unwrap_or(self.start)
+    }
+
+    /// Zero for running logs, see [`Log::until`].
+    ///
+    /// ```
+    /// let source = std::fs::read_to_string("example.md").unwrap();
+    /// let (logs, _) = djot_log::parse_log(&source);
+    /// assert_eq!(logs[0].duration(), chrono::TimeDelta::try_hours(4).unwrap());
+    /// assert_eq!(
+    ///     logs[0].kinds().paths(),
+    ///     vec![vec!["Coding"], vec!["Work", "MyOrg", "MyDept", "MyProj"]]
+    /// );
+    /// ```
+    pub fn duration(&self) -> chrono::TimeDelta {
+        self.end_or_start() - self.start
+    }
+
+    /// The time spent on each of the days this log spans.
+    fn durations_by_day(&self) -> Vec<(naive::NaiveDate, chrono::TimeDelta)> {
+        let end = self.end_or_start();
+        let mut durations = vec![];
+        let mut start = self.start;
+        while start.date() < end.date() {
+            let midnight = start
+                .date()
+                .succ_opt()
+                .unwrap()
+                .and_time(naive::NaiveTime::MIN);
+            durations.push((start.date(), midnight - start));
+            start = midnight;
+        }
+        if start < end || durations.is_empty() {
+            durations.push((start.date(), end - start));
+        }
+        durations
     }
 }
 
+/// Days between two optional bounds, both included.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DateRange {
+    pub from: Option<naive::NaiveDate>,
+    pub to: Option<naive::NaiveDate>,
+}
+
+impl DateRange {
+    pub fn day(date: naive::NaiveDate) -> DateRange {
+        DateRange {
+            from: Some(date),
+            to: Some(date),
+        }
+    }
+
+    /// Parses an ISO week such as `2024-W05`.
+    ///
+    /// ```
+    /// let week = djot_log::DateRange::week("2024-W01").unwrap();
+    /// assert_eq!(week.to_string(), "2024-01-01 to 2024-01-07");
+    /// ```
+    pub fn week(s: &str) -> Option<DateRange> {
+        let from = naive::NaiveDate::parse_from_str(&format!("{}-1", s), "%G-W%V-%u").ok()?;
+        Some(DateRange {
+            from: Some(from),
+            to: Some(from + chrono::TimeDelta::try_days(6)?),
+        })
+    }
+
+    /// Parses a calendar month such as `2024-02`.
+    ///
+    /// ```
+    /// let month = djot_log::DateRange::month("2024-02").unwrap();
+    /// assert_eq!(month.to_string(), "2024-02-01 to 2024-02-29");
+    /// ```
+    pub fn month(s: &str) -> Option<DateRange> {
+        let from = naive::NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d").ok()?;
+        Some(DateRange {
+            from: Some(from),
+            to: Some(
+                from.checked_add_months(chrono::Months::new(1))?
+                    .pred_opt()?,
+            ),
+        })
+    }
+
+    pub fn contains(&self, date: naive::NaiveDate) -> bool {
+        self.from.is_none_or(|f| date >= f) && self.to.is_none_or(|t| date <= t)
+    }
+}
+
+impl std::fmt::Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from == to => write!(f, "{}", from),
+            (Some(from), Some(to)) => write!(f, "{} to {}", from, to),
+            (Some(from), None) => write!(f, "{} onwards", from),
+            (None, Some(to)) => write!(f, "until {}", to),
+            (None, None) => write!(f, "all days"),
+        }
+    }
+}
+
+/// How to count the time of logs that cross midnight.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DayAttribution {
+    /// All of the time counts towards the day the log starts.
+    StartDay,
+    /// The time is split at midnight between the days the log spans.
+    Split,
+}
+
 #[derive(PartialEq, Eq, Hash, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Kinds {
     paths: frozenset::FrozenSet<Vec<String>>,
 }
 
 impl Kinds {
-    fn new(paths: HashSet<Vec<String>>) -> Kinds {
+    pub fn new(paths: HashSet<Vec<String>>) -> Kinds {
         Kinds {
             paths: paths.freeze(),
         }
     }
+
+    /// The kind paths, sorted.
+    pub fn paths(&self) -> Vec<&[String]> {
+        let mut paths = self.paths.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
 }
 
 impl std::fmt::Display for Kinds {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut paths = self.paths.iter().map(|p| p.join(" / ")).collect::<Vec<_>>();
-        paths.sort();
-        write!(f, "{}", paths.join(" // "))
+        write!(
+            f,
+            "{}",
+            self.paths().iter().map(|p| p.join(" / ")).join(" // ")
+        )
     }
 }
 
 impl std::fmt::Display for Log {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}-{} {}", self.start, self.end.time(), self.kinds)
+        match self.end {
+            None => write!(f, "{}- {} (running)", self.start, self.kinds),
+            Some(end) if end.date() == self.start.date() => {
+                write!(f, "{}-{} {}", self.start, end.time(), self.kinds)
+            }
+            Some(end) => write!(f, "{}-{} {}", self.start, end, self.kinds),
+        }
     }
 }
 
+/// The time of the logs on each day, one row per day in order of the days.
+///
+/// ```
+/// use djot_log::DayAttribution;
+/// let (logs, _) = djot_log::parse_log("# 2023-12-03\n## 22:00\n### Work\n## 01:30\n");
+/// assert_eq!(
+///     format!("{}", logs[0]),
+///     "2023-12-03 22:00:00-2023-12-04 01:30:00 Work"
+/// );
+/// let minutes = |attribution| {
+///     djot_log::total_by_day(logs.iter(), attribution)
+///         .iter()
+///         .map(|(d, t)| (d.to_string(), t.num_minutes()))
+///         .collect::<Vec<_>>()
+/// };
+/// assert_eq!(
+///     minutes(DayAttribution::StartDay),
+///     vec![("2023-12-03".to_string(), 210)]
+/// );
+/// assert_eq!(
+///     minutes(DayAttribution::Split),
+///     vec![("2023-12-03".to_string(), 120), ("2023-12-04".to_string(), 90)]
+/// );
+///
+/// // Logs of the same day are added up even if they are not next to each other.
+/// let (logs, _) = djot_log::parse_log(
+///     &[
+///         "# 2023-12-04\n## 09:00\n### Work\n## 10:00\n",
+///         "# 2023-12-03\n## 09:00\n### Work\n## 10:00\n",
+///         "# 2023-12-04\n## 11:00\n### Work\n## 12:00\n",
+///     ]
+///     .concat(),
+/// );
+/// assert_eq!(
+///     djot_log::total_by_day(logs.iter(), DayAttribution::StartDay)
+///         .iter()
+///         .map(|(d, t)| (d.to_string(), t.num_minutes()))
+///         .collect::<Vec<_>>(),
+///     vec![("2023-12-03".to_string(), 60), ("2023-12-04".to_string(), 120)]
+/// );
+/// ```
 pub fn total_by_day<'a>(
     logs: impl Iterator<Item = &'a Log>,
+    attribution: DayAttribution,
 ) -> Vec<(naive::NaiveDate, chrono::TimeDelta)> {
-    logs.group_by(|l| l.start.date())
-        .into_iter()
-        .map(|(d, ls)| (d, ls.map(|l| l.duration()).sum()))
-        .collect()
+    let mut days = BTreeMap::new();
+    for (date, duration) in logs.flat_map(|l| match attribution {
+        DayAttribution::StartDay => vec![(l.start.date(), l.duration())],
+        DayAttribution::Split => l.durations_by_day(),
+    }) {
+        *days.entry(date).or_default() += duration;
+    }
+    days.into_iter().collect()
+}
+
+/// Adds the days in `range` that have a target but no logs, with no time, so that they count
+/// against the balance.
+///
+/// Unbounded ends of the range default to the first and last days in `totals`. Rows for the same
+/// day, such as from a repeated day header, are added together.
+///
+/// ```
+/// let source = std::fs::read_to_string("example.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// let totals = djot_log::total_by_day(logs.iter(), djot_log::DayAttribution::StartDay);
+/// let schedule: djot_log::schedule::Schedule = "mon-fri=8".parse().unwrap();
+/// let range = djot_log::DateRange::week("2023-W49").unwrap();
+/// assert_eq!(
+///     djot_log::add_missing_days(&totals, range, &schedule)
+///         .iter()
+///         .map(|(date, total)| (date.to_string(), total.num_hours()))
+///         .collect::<Vec<_>>(),
+///     vec![
+///         ("2023-12-03".to_string(), 8),
+///         ("2023-12-04".to_string(), 8),
+///         ("2023-12-05".to_string(), 0),
+///         ("2023-12-06".to_string(), 0),
+///         ("2023-12-07".to_string(), 0),
+///         ("2023-12-08".to_string(), 0),
+///     ]
+/// );
+/// let (logs, _) = djot_log::parse_log(&format!("{}\n{}", source, source));
+/// let totals = djot_log::total_by_day(logs.iter(), djot_log::DayAttribution::StartDay);
+/// assert_eq!(
+///     djot_log::add_missing_days(&totals, range, &schedule)[1].1.num_hours(),
+///     16
+/// );
+/// ```
+pub fn add_missing_days(
+    totals: &[(naive::NaiveDate, chrono::TimeDelta)],
+    range: DateRange,
+    target: &impl schedule::DailyTarget,
+) -> Vec<(naive::NaiveDate, chrono::TimeDelta)> {
+    let mut days = BTreeMap::new();
+    for (date, total) in totals {
+        *days.entry(*date).or_default() += *total;
+    }
+    let from = range.from.or(days.keys().next().copied());
+    let to = range.to.or(days.keys().next_back().copied());
+    if let (Some(from), Some(to)) = (from, to) {
+        for date in from.iter_days().take_while(|d| *d <= to) {
+            if !target.target(date).is_zero() {
+                days.entry(date).or_insert(chrono::TimeDelta::zero());
+            }
+        }
+    }
+    days.into_iter().collect()
 }
 
 pub fn add_running_total<'a>(
@@ -64,16 +374,159 @@ pub fn add_running_total<'a>(
     })
 }
 
-pub fn target(incr: chrono::TimeDelta) -> impl Iterator<Item = chrono::TimeDelta> {
-    std::ops::RangeFrom { start: 1 }.map(move |i| incr * i)
+/// Compares the running total of each day with the sum of the targets of the days so far.
+///
+/// ```
+/// let source = std::fs::read_to_string("example_unbalanced.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// let totals = djot_log::total_by_day(logs.iter(), djot_log::DayAttribution::StartDay);
+/// let schedule: djot_log::schedule::Schedule = "mon-thu=8,fri-sun=6".parse().unwrap();
+/// assert_eq!(
+///     djot_log::running_total_vs_target(djot_log::add_running_total(totals.iter()), &schedule)
+///         .map(|(date, _, delta)| (date.to_string(), delta.num_minutes()))
+///         .collect::<Vec<_>>(),
+///     vec![
+///         ("2023-12-03".to_string(), 120),
+///         ("2023-12-04".to_string(), 120),
+///         ("2023-12-05".to_string(), 130),
+///         ("2023-12-06".to_string(), 120),
+///     ]
+/// );
+/// ```
+pub fn running_total_vs_target<'a>(
+    logs: impl Iterator<Item = (naive::NaiveDate, chrono::TimeDelta, chrono::TimeDelta)> + 'a,
+    target: &'a impl schedule::DailyTarget,
+) -> impl Iterator<Item = (naive::NaiveDate, chrono::TimeDelta, chrono::TimeDelta)> + 'a {
+    logs.scan(
+        chrono::TimeDelta::zero(),
+        move |running_target, (date, total, running)| {
+            *running_target += target.target(date);
+            Some((date, total, running - *running_target))
+        },
+    )
+}
+
+/// Shifts the differences returned by [`running_total_vs_target`] to start from `opening`, and to
+/// restart from a known balance at the start of each day in `resets`.
+///
+/// A reset on a day that is not in `rows` applies from the next day that is.
+///
+/// ```
+/// let source = std::fs::read_to_string("example_unbalanced.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// let totals = djot_log::total_by_day(logs.iter(), djot_log::DayAttribution::StartDay);
+/// let schedule = djot_log::schedule::Schedule::flat(chrono::TimeDelta::try_hours(8).unwrap());
+/// let rows = djot_log::running_total_vs_target(djot_log::add_running_total(totals.iter()), &schedule);
+/// let hours = |h| chrono::TimeDelta::try_hours(h).unwrap();
+/// let settled = chrono::NaiveDate::from_ymd_opt(2023, 12, 6).unwrap();
+/// assert_eq!(
+///     djot_log::with_balance_resets(rows, hours(12), &[(settled, hours(1))].into())
+///         .map(|(date, _, delta)| (date.to_string(), delta.num_minutes()))
+///         .collect::<Vec<_>>(),
+///     vec![
+///         ("2023-12-03".to_string(), 720),
+///         ("2023-12-04".to_string(), 720),
+///         ("2023-12-05".to_string(), 730),
+///         ("2023-12-06".to_string(), 50),
+///     ]
+/// );
+/// ```
+pub fn with_balance_resets<'a>(
+    rows: impl Iterator<Item = (naive::NaiveDate, chrono::TimeDelta, chrono::TimeDelta)> + 'a,
+    opening: chrono::TimeDelta,
+    resets: &'a BTreeMap<naive::NaiveDate, chrono::TimeDelta>,
+) -> impl Iterator<Item = (naive::NaiveDate, chrono::TimeDelta, chrono::TimeDelta)> + 'a {
+    let mut resets = resets.iter().peekable();
+    rows.scan(
+        (opening, chrono::TimeDelta::zero()),
+        move |(offset, previous), (date, total, vs_target)| {
+            while let Some((_, balance)) = resets.next_if(|(reset, _)| **reset <= date) {
+                *offset = *balance - *previous;
+            }
+            *previous = vs_target;
+            Some((date, total, vs_target + *offset))
+        },
+    )
 }
 
-pub fn running_total_vs_target(
-    logs: impl Iterator<Item = (naive::NaiveDate, chrono::TimeDelta, chrono::TimeDelta)>,
-    target: impl Iterator<Item = chrono::TimeDelta>,
-) -> impl Iterator<Item = (naive::NaiveDate, chrono::TimeDelta, chrono::TimeDelta)> {
-    logs.zip(target)
-        .map(|((date, total, running), target)| (date, total, running - target))
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    TimeHeaderWithoutDay {
+        position: md::Position,
+    },
+    KindHeaderWithoutStartTime {
+        position: md::Position,
+    },
+    /// An entry with kinds that was not closed by a time header before the next day header.
+    UnterminatedEntry {
+        position: md::Position,
+    },
+}
+
+impl ParseError {
+    pub fn position(&self) -> md::Position {
+        match self {
+            ParseError::TimeHeaderWithoutDay { position }
+            | ParseError::KindHeaderWithoutStartTime { position }
+            | ParseError::UnterminatedEntry { position } => *position,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::TimeHeaderWithoutDay { .. } => {
+                write!(f, "time header without preceding day header")
+            }
+            ParseError::KindHeaderWithoutStartTime { .. } => {
+                write!(f, "kind header without start time")
+            }
+            ParseError::UnterminatedEntry { .. } => {
+                write!(f, "unterminated entry, add a time header to end it")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An input format that yields the day, time and kind headers of a log.
+///
+/// Implementors only need to produce [`md::LogNode`]s; the conversion into [`Log`]s is shared.
+///
+/// ```
+/// use djot_log::md::{Located, LogNode, Position};
+/// use djot_log::LogSource;
+///
+/// /// One header per line, with its depth given as a number of `>`.
+/// struct Quoted;
+///
+/// impl LogSource for Quoted {
+///     fn log_nodes(&self, s: &str) -> Vec<Located<LogNode>> {
+///         s.lines()
+///             .enumerate()
+///             .flat_map(|(i, l)| {
+///                 let text = l.trim_start_matches('>');
+///                 Some(Located {
+///                     value: LogNode::from_heading((l.len() - text.len()) as u8, text.trim())?,
+///                     position: Position { line: i + 1, column: 1 },
+///                 })
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// let (logs, errors) = Quoted.parse_log("> 2023-12-03\n>> 09:00\n>>> Work\n>> 17:00\n");
+/// assert!(errors.is_empty());
+/// assert_eq!(format!("{}", logs[0]), "2023-12-03 09:00:00-17:00:00 Work");
+/// ```
+pub trait LogSource {
+    fn log_nodes(&self, s: &str) -> Vec<md::Located<md::LogNode>>;
+
+    fn parse_log(&self, s: &str) -> (Vec<Log>, Vec<ParseError>) {
+        logs_from_nodes(self.log_nodes(s))
+    }
 }
 
 ///
@@ -93,47 +546,145 @@ pub fn running_total_vs_target(
 /// 2023-12-04 14:00:00-18:00:00 Coding // Work / MyOrg / MyDept / MyProj"
 /// )
 /// ```
-pub fn parse_log(s: &str) -> (Vec<Log>, Vec<String>) {
+///
+/// ```
+/// assert_eq!(
+///     djot_log::parse_log("# 2023-12-03\n\n### Work\n").1,
+///     vec![djot_log::ParseError::KindHeaderWithoutStartTime {
+///         position: djot_log::md::Position { line: 3, column: 1 }
+///     }]
+/// );
+/// ```
+///
+/// A day header ends any open entry, so the first time header of a day never closes an entry
+/// from the previous day:
+///
+/// ```
+/// let (logs, errors) = djot_log::parse_log(
+///     "# 2023-12-03\n## 17:00\n### Work\n# 2023-12-04\n## 09:00\n### Work\n## 10:00\n",
+/// );
+/// assert_eq!(
+///     errors,
+///     vec![djot_log::ParseError::UnterminatedEntry {
+///         position: djot_log::md::Position { line: 2, column: 1 }
+///     }]
+/// );
+/// assert_eq!(format!("{}", logs[0]), "2023-12-04 09:00:00-10:00:00 Work");
+/// ```
+pub fn parse_log(s: &str) -> (Vec<Log>, Vec<ParseError>) {
+    md::Markdown.parse_log(s)
+}
+
+/// An error for the in-progress entry at the end of `logs` if it started before the day of `now`,
+/// as it was most likely left open by mistake.
+///
+/// ```
+/// use djot_log::LogSource;
+/// let source = "# 2023-12-04\n\n## 08:00\n\n### Work\n";
+/// let nodes = djot_log::md::Markdown.log_nodes(source);
+/// let (logs, _) = djot_log::logs_from_nodes(nodes.iter().cloned());
+/// let at = |s| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+/// assert_eq!(djot_log::unterminated_running_entry(&nodes, &logs, at("2023-12-04 12:00")), None);
+/// assert_eq!(
+///     djot_log::unterminated_running_entry(&nodes, &logs, at("2023-12-05 12:00")),
+///     Some(djot_log::ParseError::UnterminatedEntry {
+///         position: djot_log::md::Position { line: 3, column: 1 }
+///     })
+/// );
+/// ```
+pub fn unterminated_running_entry(
+    nodes: &[md::Located<md::LogNode>],
+    logs: &[Log],
+    now: naive::NaiveDateTime,
+) -> Option<ParseError> {
+    logs.last()
+        .filter(|log| log.is_running() && log.start.date() < now.date())?;
+    // The running entry starts at the last time header.
+    nodes.iter().rev().find_map(|node| match node.value {
+        md::LogNode::TimeHeader(_) => Some(ParseError::UnterminatedEntry {
+            position: node.position,
+        }),
+        _ => None,
+    })
+}
+
+pub fn logs_from_nodes(
+    nodes: impl IntoIterator<Item = md::Located<md::LogNode>>,
+) -> (Vec<Log>, Vec<ParseError>) {
     let mut current_day: Option<naive::NaiveDate> = None;
     let mut start_time: Option<naive::NaiveDateTime> = None;
-    let mut errors: Vec<String> = vec![];
+    let mut start_position = md::Position { line: 1, column: 1 };
+    let mut errors: Vec<ParseError> = vec![];
     let mut kinds = HashSet::new();
+    let mut notes = Vec::new();
     let mut logs = Vec::new();
-    for n in md::parse_log_nodes(&md::parse_markdown(s)) {
-        match n {
+    for md::Located { value, position } in nodes {
+        match value {
             md::LogNode::DayHeader(md::DayHeader { date }) => {
+                if !kinds.is_empty() {
+                    errors.push(ParseError::UnterminatedEntry {
+                        position: start_position,
+                    });
+                    kinds = HashSet::new();
+                }
+                notes = Vec::new();
                 current_day = Some(date);
+                start_time = None;
             }
             md::LogNode::TimeHeader(md::TimeHeader { time }) => match start_time {
                 None => match current_day {
                     Some(current_day) => {
                         start_time = Some(naive::NaiveDateTime::new(current_day, time));
+                        start_position = position;
                     }
                     None => {
-                        errors.push(format!("Unexpected {:?} without preceding day header", n));
+                        errors.push(ParseError::TimeHeaderWithoutDay { position });
                     }
                 },
                 Some(start_time_) => {
-                    let end = naive::NaiveDateTime::new(current_day.unwrap(), time);
+                    let mut end = naive::NaiveDateTime::new(current_day.unwrap(), time);
+                    if end < start_time_ {
+                        // The interval crossed midnight, so later times belong to the next day
+                        end += chrono::TimeDelta::try_days(1).unwrap();
+                        current_day = Some(end.date());
+                    }
                     if !kinds.is_empty() {
                         logs.push(Log {
                             start: start_time_,
-                            end,
+                            end: Some(end),
                             kinds: Kinds::new(kinds),
+                            notes,
                         });
                         kinds = HashSet::new();
                     }
+                    notes = Vec::new();
                     start_time = Some(end);
+                    start_position = position;
                 }
             },
-            md::LogNode::KindHeader(md::KindHeader { ref path }) => match start_time {
+            md::LogNode::KindHeader(md::KindHeader { path }) => match start_time {
                 Some(_) => {
-                    kinds.insert(path.clone());
+                    kinds.insert(path);
                 }
                 None => {
-                    errors.push(format!("Unexpected {:?} without start time set", n));
+                    errors.push(ParseError::KindHeaderWithoutStartTime { position });
                 }
             },
+            md::LogNode::Note(note) => {
+                if start_time.is_some() {
+                    notes.push(note);
+                }
+            }
+        }
+    }
+    if let Some(start) = start_time {
+        if !kinds.is_empty() {
+            logs.push(Log {
+                start,
+                end: None,
+                kinds: Kinds::new(kinds),
+                notes,
+            });
         }
     }
     (logs, errors)
@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+use chrono::naive;
+
+use crate::md::{Located, LogNode, Position};
+
+/// A structural mistake in a log that [`crate::logs_from_nodes`] accepts without an error.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Problem {
+    /// A day header with the same day as an earlier one.
+    RepeatedDay { position: Position },
+    /// A day header with a day before that of an earlier one.
+    DayOutOfOrder { position: Position },
+    /// A time header before the previous one that does not look like a midnight crossing: the
+    /// times of its day already crossed midnight once, or the entry it would end is 12 hours or
+    /// longer.
+    TimeOutOfOrder { position: Position },
+    /// A time header at the same time as the previous one, ending an entry of no time.
+    ZeroLengthEntry { position: Position },
+    /// A time header that neither ends nor starts an entry, as there are no kinds on either side.
+    UnusedTimeHeader { position: Position },
+    /// A kind header after the last time header of the log, so its entry is still running.
+    RunningEntry { position: Position },
+}
+
+impl Problem {
+    pub fn position(&self) -> Position {
+        match self {
+            Problem::RepeatedDay { position }
+            | Problem::DayOutOfOrder { position }
+            | Problem::TimeOutOfOrder { position }
+            | Problem::ZeroLengthEntry { position }
+            | Problem::UnusedTimeHeader { position }
+            | Problem::RunningEntry { position } => *position,
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::RepeatedDay { .. } => write!(f, "day header repeated"),
+            Problem::DayOutOfOrder { .. } => write!(f, "day header before the previous day"),
+            Problem::TimeOutOfOrder { .. } => write!(f, "time header before the previous time"),
+            Problem::ZeroLengthEntry { .. } => write!(f, "entry of zero length"),
+            Problem::UnusedTimeHeader { .. } => {
+                write!(f, "time header without kinds before or after it")
+            }
+            Problem::RunningEntry { .. } => {
+                write!(
+                    f,
+                    "kind header after the last time header, the entry is still running"
+                )
+            }
+        }
+    }
+}
+
+/// The longest entry that a time header before the previous one is taken to end past midnight.
+const MAX_CROSSING_HOURS: i64 = 12;
+
+/// The time headers of a day, and the position of the first kind header after each of them.
+type Day = Vec<(naive::NaiveTime, Position, Option<Position>)>;
+
+/// Finds the problems in the nodes of a log, in the order of the nodes.
+///
+/// ```
+/// use djot_log::{lint, LogSource};
+/// let source = "\
+/// ## 2023-12-04
+///
+/// ### 13:00
+///
+/// #### Work
+///
+/// ### 09:00
+///
+/// #### Work
+///
+/// ### 10:00
+///
+/// ### 09:30
+///
+/// #### Work
+///
+/// ### 09:45
+///
+/// ## 2023-12-05
+///
+/// ### 22:00
+///
+/// #### Work
+///
+/// ### 01:30
+///
+/// #### Other
+///
+/// ### 03:00
+///
+/// ## 2023-12-03
+///
+/// ### 08:00
+///
+/// ### 09:00
+///
+/// #### Work
+/// ";
+/// let problems = lint::check(&djot_log::md::Markdown.log_nodes(source));
+/// assert_eq!(
+///     problems.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+///     vec![
+///         "time header before the previous time",
+///         "time header before the previous time",
+///         "day header before the previous day",
+///         "time header without kinds before or after it",
+///         "kind header after the last time header, the entry is still running",
+///     ]
+/// );
+/// assert!(lint::check(&djot_log::md::Markdown.log_nodes(
+///     &std::fs::read_to_string("example.md").unwrap()
+/// ))
+/// .is_empty());
+/// ```
+pub fn check(nodes: &[Located<LogNode>]) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut seen = HashSet::new();
+    let mut latest = None;
+    let mut day = None;
+    for node in nodes {
+        match &node.value {
+            LogNode::DayHeader(header) => {
+                if let Some(day) = day.take() {
+                    check_day(day, false, &mut problems);
+                }
+                if !seen.insert(header.date) {
+                    problems.push(Problem::RepeatedDay {
+                        position: node.position,
+                    });
+                } else if latest.is_some_and(|latest| header.date < latest) {
+                    problems.push(Problem::DayOutOfOrder {
+                        position: node.position,
+                    });
+                }
+                latest = latest.max(Some(header.date));
+                day = Some(vec![]);
+            }
+            LogNode::TimeHeader(header) => {
+                if let Some(day) = day.as_mut() {
+                    day.push((header.time, node.position, None));
+                }
+            }
+            LogNode::KindHeader(_) => {
+                if let Some((_, _, kinds @ None)) = day.as_mut().and_then(|d| d.last_mut()) {
+                    *kinds = Some(node.position);
+                }
+            }
+            LogNode::Note(_) => {}
+        }
+    }
+    if let Some(day) = day {
+        check_day(day, true, &mut problems);
+    }
+    problems
+}
+
+fn check_day(times: Day, last: bool, problems: &mut Vec<Problem>) {
+    // As in `logs_from_nodes`, a time before the previous one is on the next day, which is only
+    // plausible once a day and for an entry that is not too long, such as 22:00 to 01:30.
+    let mut crossed_midnight = false;
+    for (i, &(time, position, kinds)) in times.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|i| times[i]);
+        if let Some((previous_time, _, previous_kinds)) = previous {
+            if time < previous_time {
+                let length = time - previous_time + chrono::TimeDelta::try_days(1).unwrap();
+                if crossed_midnight
+                    || length >= chrono::TimeDelta::try_hours(MAX_CROSSING_HOURS).unwrap()
+                {
+                    problems.push(Problem::TimeOutOfOrder { position });
+                }
+                crossed_midnight = true;
+            } else if time == previous_time && previous_kinds.is_some() {
+                problems.push(Problem::ZeroLengthEntry { position });
+            }
+        }
+        let ends_entry = previous.is_some_and(|(_, _, kinds)| kinds.is_some());
+        let starts_entry = kinds.is_some();
+        if !ends_entry && !starts_entry {
+            problems.push(Problem::UnusedTimeHeader { position });
+        }
+        if let (true, true, Some(position)) = (last, i == times.len() - 1, kinds) {
+            problems.push(Problem::RunningEntry { position });
+        }
+    }
+}
@@ -1,67 +1,689 @@
 use std::error;
 
 use clap::Parser;
+use djot_log::LogSource;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
     file: std::path::PathBuf,
 
-    #[arg(long, default_value_t = 8)]
+    /// Input format, defaults to Djot for .dj and .djot files and Markdown otherwise
+    #[arg(long, value_enum, global = true)]
+    format: Option<Format>,
+
+    /// Hours to work every day
+    #[arg(long, default_value_t = 8, global = true)]
     hours_target: i64,
 
-    /// Day to show logs for, defaults to today
+    /// Hours to work on each day of the week, such as mon-thu=8,fri=6; other days have no target
+    #[arg(long, global = true, conflicts_with = "hours_target")]
+    schedule: Option<djot_log::schedule::Schedule>,
+
+    /// File with a schedule per line, each optionally preceded by the day it takes effect
+    #[arg(long, global = true, conflicts_with_all = ["hours_target", "schedule"])]
+    schedule_file: Option<std::path::PathBuf>,
+
+    /// Balance carried over before the first day, in hours, such as 12 or -3.5
+    #[arg(long, global = true, value_parser = djot_log::schedule::parse_hours, allow_hyphen_values = true)]
+    opening_balance: Option<chrono::TimeDelta>,
+
+    /// Day on which the balance was settled, optionally with the balance in hours it restarts from
+    /// at the start of that day, such as 2024-01-01 or 2024-01-01=2.5; can be repeated
+    #[arg(long, global = true, value_parser = djot_log::schedule::parse_settlement)]
+    settled: Vec<(chrono::NaiveDate, chrono::TimeDelta)>,
+
+    /// File with an opening line with the opening balance, and settled lines with the days on
+    /// which the balance was settled; --opening-balance and --settled take precedence
+    #[arg(long, global = true)]
+    balance_file: Option<std::path::PathBuf>,
+
+    /// File with days off, which have no target; can be repeated
+    #[arg(long, global = true)]
+    calendar: Vec<std::path::PathBuf>,
+
+    /// Split logs that cross midnight between both days, instead of counting them towards the day
+    /// they start
+    #[arg(long, global = true)]
+    split_midnight: bool,
+
+    /// First day to include
+    #[arg(long, global = true, conflicts_with_all = ["week", "month"])]
+    from: Option<chrono::NaiveDate>,
+
+    /// Last day to include
+    #[arg(long, global = true, conflicts_with_all = ["week", "month"])]
+    to: Option<chrono::NaiveDate>,
+
+    /// ISO week to include, such as 2024-W05
+    #[arg(long, global = true, value_parser = parse_week, conflicts_with = "month")]
+    week: Option<djot_log::DateRange>,
+
+    /// Month to include, such as 2024-01
+    #[arg(long, global = true, value_parser = parse_month)]
+    month: Option<djot_log::DateRange>,
+
+    /// Day to show logs for, defaults to the included days if limited, or today
     #[arg(long)]
     show: Option<String>,
+
+    /// Print the notes of each entry under it
+    #[arg(long)]
+    notes: bool,
+
+    #[arg(long, value_enum, default_value_t = Output::Text, global = true)]
+    output: Output,
+
+    /// Without a command, prints the balance and the logs of a day
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Write every entry as CSV
+    Export {
+        /// Write the total of each day instead of the entries
+        #[arg(long)]
+        totals: bool,
+    },
+    /// Show the time spent on each kind path, and on each of its subpaths
+    Report,
+    /// Show the time spent on each combination of two dimensions of kinds, such as projects and
+    /// activities
+    Pivot {
+        /// Root of the kind paths to use as rows, can be repeated
+        #[arg(long = "row", required = true)]
+        rows: Vec<String>,
+
+        /// Root of the kind paths to use as columns, can be repeated
+        #[arg(long = "column", required = true)]
+        columns: Vec<String>,
+    },
+    /// Show the total time, target and difference of each week, month or year
+    Summary {
+        /// Length of the periods to add up
+        #[arg(long, value_enum, default_value_t = Period::Week)]
+        by: Period,
+    },
+    /// Report structural problems in the log, exiting with an error if there are any
+    #[command(alias = "lint")]
+    Check,
+    /// Rewrite the log to fix mechanical mistakes, such as days out of order or times written as
+    /// 9:00
+    Fix,
+    /// Rewrite the log in the canonical format, with the same spacing and order of headings
+    Fmt,
+    /// Start an entry now, ending the running entry if there is one
+    Start {
+        /// Kind path of the entry, such as Work / MyOrg / MyProj
+        #[arg(required = true, num_args = 1..)]
+        path: Vec<String>,
+
+        /// Another kind of the entry, can be repeated
+        #[arg(long = "kind")]
+        kinds: Vec<String>,
+    },
+    /// End the running entry now
+    Stop,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Period {
+    Week,
+    Month,
+    Year,
+}
+
+impl From<Period> for djot_log::summary::Period {
+    fn from(period: Period) -> djot_log::summary::Period {
+        match period {
+            Period::Week => djot_log::summary::Period::Week,
+            Period::Month => djot_log::summary::Period::Month,
+            Period::Year => djot_log::summary::Period::Year,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Output {
+    Text,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Markdown,
+    Djot,
+}
+
+impl Format {
+    fn from_path(path: &std::path::Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("dj" | "djot") => Format::Djot,
+            _ => Format::Markdown,
+        }
+    }
+
+    fn source(self) -> &'static dyn LogSource {
+        match self {
+            Format::Markdown => &djot_log::md::Markdown,
+            Format::Djot => &djot_log::djot::Djot,
+        }
+    }
+}
+
+fn parse_week(s: &str) -> Result<djot_log::DateRange, String> {
+    djot_log::DateRange::week(s).ok_or_else(|| format!("expected a week such as 2024-W05: {}", s))
+}
+
+fn parse_month(s: &str) -> Result<djot_log::DateRange, String> {
+    djot_log::DateRange::month(s).ok_or_else(|| format!("expected a month such as 2024-01: {}", s))
+}
+
+/// Prints an error pointing at the offending line of the log, in the style of compiler errors.
+fn print_diagnostic(
+    path: &std::path::Path,
+    source: &str,
+    message: &dyn std::fmt::Display,
+    position: djot_log::md::Position,
+) {
+    let line = source.lines().nth(position.line - 1).unwrap_or_default();
+    let gutter = " ".repeat(position.line.to_string().len());
+    let marker_width = line
+        .chars()
+        .count()
+        .saturating_sub(position.column - 1)
+        .max(1);
+    eprintln!("error: {}", message);
+    eprintln!("{}--> {}:{}", gutter, path.display(), position);
+    eprintln!("{} |", gutter);
+    eprintln!("{} | {}", position.line, line);
+    eprintln!(
+        "{} | {}{}",
+        gutter,
+        " ".repeat(position.column - 1),
+        "^".repeat(marker_width)
+    );
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
     let args = Args::parse();
     env_logger::init();
-    let source = std::fs::read_to_string(args.file)?;
-    let (logs, errors) = djot_log::parse_log(&source);
-    if !errors.is_empty() {
-        log::error!("{:?}", errors);
+    let format = args.format.unwrap_or_else(|| Format::from_path(&args.file));
+    let source = match std::fs::read_to_string(&args.file) {
+        Err(e)
+            if e.kind() == std::io::ErrorKind::NotFound
+                && matches!(args.command, Some(Command::Start { .. })) =>
+        {
+            String::new()
+        }
+        source => source?,
+    };
+    let now = chrono::Local::now().naive_local();
+    let nodes = format.source().log_nodes(&source);
+    let (logs, mut errors) = djot_log::logs_from_nodes(nodes.iter().cloned());
+    errors.extend(djot_log::unterminated_running_entry(&nodes, &logs, now));
+    for error in errors.iter() {
+        print_diagnostic(&args.file, &source, error, error.position());
     }
 
-    println!("Balance:");
-    println!();
+    match &args.command {
+        Some(Command::Check) => {
+            let problems = djot_log::lint::check(&nodes);
+            for problem in problems.iter() {
+                print_diagnostic(&args.file, &source, problem, problem.position());
+            }
+            if !errors.is_empty() || !problems.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Fix) => rewrite(&args.file, &source, djot_log::fix::fix(&source, &nodes))?,
+        Some(Command::Fmt) => rewrite(
+            &args.file,
+            &source,
+            djot_log::format::format(&source, &nodes),
+        )?,
+        Some(Command::Start { path, kinds }) => append(
+            &args.file,
+            &djot_log::clock::start(
+                &source,
+                &nodes,
+                now,
+                &std::iter::once(path.join(" "))
+                    .chain(kinds.iter().cloned())
+                    .flat_map(|k| djot_log::md::KindHeader::parse(&k))
+                    .map(|k| k.path)
+                    .collect::<Vec<_>>(),
+            )?,
+        )?,
+        Some(Command::Stop) => append(&args.file, &djot_log::clock::stop(&source, &nodes, now)?)?,
+        Some(Command::Export { totals }) => {
+            let days = Days::new(&args, logs, now)?;
+            if *totals {
+                djot_log::export::write_totals_csv(&days.total_by_day, std::io::stdout())?;
+            } else {
+                djot_log::export::write_logs_csv(days.logs_until_now.iter(), std::io::stdout())?;
+            }
+        }
+        Some(Command::Report) => {
+            let days = Days::new(&args, logs, now)?;
+            let tree = djot_log::report::kind_tree(days.logs_until_now.iter());
+            match args.output {
+                Output::Text => print_kind_tree(&tree, tree.total, 0),
+                Output::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&kind_tree_json("", &tree, tree.total))?
+                ),
+            }
+        }
+        Some(Command::Pivot { rows, columns }) => {
+            let days = Days::new(&args, logs, now)?;
+            let pivot = djot_log::report::pivot(
+                days.logs_until_now.iter(),
+                &dimension(rows),
+                &dimension(columns),
+            );
+            match args.output {
+                Output::Text => print_pivot(&pivot),
+                Output::Json => println!("{}", serde_json::to_string_pretty(&pivot_json(&pivot))?),
+            }
+        }
+        Some(Command::Summary { by }) => {
+            let days = Days::new(&args, logs, now)?;
+            let summaries = djot_log::summary::summarize(
+                &days.total_by_day_with_missing,
+                (*by).into(),
+                &days.target(),
+            );
+            match args.output {
+                Output::Text => {
+                    for summary in summaries.iter() {
+                        println!(
+                            "{}: {}, target {}, delta minutes {}, days worked {}",
+                            summary.period,
+                            format_duration(summary.total),
+                            format_duration(summary.target),
+                            summary.difference().num_minutes(),
+                            summary.days_worked
+                        );
+                    }
+                }
+                Output::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(
+                        &summaries
+                            .iter()
+                            .map(|s| serde_json::json!({
+                                "period": s.period,
+                                "total_minutes": s.total.num_minutes(),
+                                "target_minutes": s.target.num_minutes(),
+                                "delta_minutes": s.difference().num_minutes(),
+                                "days_worked": s.days_worked,
+                            }))
+                            .collect::<Vec<_>>()
+                    )?
+                ),
+            }
+        }
+        None => print_balance(&args, &Days::new(&args, logs, now)?, now)?,
+    }
 
-    let total_by_day = djot_log::total_by_day(logs.iter());
-    let total_by_day_with_running = djot_log::add_running_total(total_by_day.iter());
-    let target = djot_log::target(chrono::TimeDelta::try_hours(args.hours_target).unwrap());
-    let total_by_day_vs_target =
-        djot_log::running_total_vs_target(total_by_day_with_running, target).collect::<Vec<_>>();
-    for (i, (date, total, vs_target)) in total_by_day_vs_target.iter().rev().enumerate() {
-        let total = total.num_minutes();
-        let (h, m) = (total / 60, total % 60);
-        println!(
-            "day: {} {}h {}m, delta minutes {}",
-            date,
-            h,
-            m,
-            vs_target.num_minutes()
-        );
-        if *vs_target == chrono::TimeDelta::zero() && i != 0 {
+    Ok(())
+}
+
+/// The logs in the chosen range of days, with the totals and targets of each day.
+struct Days {
+    range: djot_log::DateRange,
+    logs: Vec<djot_log::Log>,
+    logs_until_now: Vec<djot_log::Log>,
+    total_by_day: Vec<(chrono::NaiveDate, chrono::TimeDelta)>,
+    /// With the days without logs that have a target, when a schedule is given.
+    total_by_day_with_missing: Vec<(chrono::NaiveDate, chrono::TimeDelta)>,
+    schedule: djot_log::schedule::DatedSchedule,
+    calendar: djot_log::calendar::Calendar,
+}
+
+impl Days {
+    fn new(
+        args: &Args,
+        logs: Vec<djot_log::Log>,
+        now: chrono::NaiveDateTime,
+    ) -> Result<Days, Box<dyn error::Error>> {
+        let range = args.week.or(args.month).unwrap_or(djot_log::DateRange {
+            from: args.from,
+            to: args.to,
+        });
+        let logs = logs
+            .into_iter()
+            .filter(|l| range.contains(l.start.date()))
+            .collect::<Vec<_>>();
+
+        let logs_until_now = logs.iter().map(|l| l.until(now)).collect::<Vec<_>>();
+
+        let attribution = if args.split_midnight {
+            djot_log::DayAttribution::Split
+        } else {
+            djot_log::DayAttribution::StartDay
+        };
+        let total_by_day = djot_log::total_by_day(logs_until_now.iter(), attribution);
+        let scheduled = args.schedule.is_some() || args.schedule_file.is_some();
+        let schedule: djot_log::schedule::DatedSchedule = match (args.schedule, &args.schedule_file)
+        {
+            (Some(schedule), _) => schedule.into(),
+            (None, Some(path)) => std::fs::read_to_string(path)?
+                .parse()
+                .map_err(|e| format!("{}: {}", path.display(), e))?,
+            (None, None) => djot_log::schedule::Schedule::flat(
+                chrono::TimeDelta::try_hours(args.hours_target).unwrap(),
+            )
+            .into(),
+        };
+        let mut calendar = djot_log::calendar::Calendar::default();
+        for path in args.calendar.iter() {
+            calendar.extend(
+                std::fs::read_to_string(path)?
+                    .parse()
+                    .map_err(|e| format!("{}: {}", path.display(), e))?,
+            );
+        }
+        // A flat target cannot tell workdays from days off, so only a schedule charges missing
+        // days. Scheduled days off are added too, so that the balance shows them.
+        let total_by_day_with_missing = if scheduled {
+            djot_log::add_missing_days(&total_by_day, range, &schedule)
+        } else {
+            total_by_day.clone()
+        };
+        Ok(Days {
+            range,
+            logs,
+            logs_until_now,
+            total_by_day,
+            total_by_day_with_missing,
+            schedule,
+            calendar,
+        })
+    }
+
+    /// The target of each day, which is zero on days off.
+    fn target(&self) -> djot_log::calendar::WithDaysOff<'_, &djot_log::schedule::DatedSchedule> {
+        self.calendar.apply(&self.schedule)
+    }
+}
+
+fn print_balance(
+    args: &Args,
+    days: &Days,
+    now: chrono::NaiveDateTime,
+) -> Result<(), Box<dyn error::Error>> {
+    let range = days.range;
+    let total_by_day_with_running =
+        djot_log::add_running_total(days.total_by_day_with_missing.iter());
+    let mut settlements = djot_log::schedule::Balance::default();
+    if let Some(path) = &args.balance_file {
+        settlements = std::fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    settlements.opening = args.opening_balance.unwrap_or(settlements.opening);
+    settlements.settled.extend(args.settled.iter().copied());
+    let total_by_day_vs_target = djot_log::with_balance_resets(
+        djot_log::running_total_vs_target(total_by_day_with_running, &days.target()),
+        settlements.opening,
+        &settlements.settled,
+    )
+    .collect::<Vec<_>>();
+    let last_reset = settlements.settled.keys().next_back();
+    let mut balance = vec![];
+    for (i, row) in total_by_day_vs_target.iter().rev().enumerate() {
+        balance.push(*row);
+        if range == djot_log::DateRange::default()
+            && ((row.2 == chrono::TimeDelta::zero() && i != 0)
+                || last_reset.is_some_and(|reset| row.0 <= *reset))
+        {
             break;
         }
     }
 
-    let show = args
-        .show
-        .map(|s| {
+    let show = match &args.show {
+        Some(s) => djot_log::DateRange::day(
             chrono::NaiveDate::parse_from_str(s.as_ref(), "%Y-%m-%d")
-                .expect("Unparseable show date")
+                .expect("Unparseable show date"),
+        ),
+        None if range != djot_log::DateRange::default() => range,
+        None => djot_log::DateRange::day(now.date()),
+    };
+    let shown_logs = days
+        .logs
+        .iter()
+        .zip(days.logs_until_now.iter())
+        .filter(|(l, _)| show.contains(l.start.date()))
+        .collect::<Vec<_>>();
+
+    match args.output {
+        Output::Text => print_text(&balance, &days.calendar, show, &shown_logs, args.notes),
+        Output::Json => print_json(&balance, &days.calendar, &shown_logs)?,
+    }
+    Ok(())
+}
+
+/// Writes the rewritten log, unless it is unchanged.
+fn rewrite(path: &std::path::Path, source: &str, rewritten: String) -> std::io::Result<()> {
+    if rewritten != source {
+        std::fs::write(path, rewritten)?;
+    }
+    Ok(())
+}
+
+fn append(path: &std::path::Path, appended: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(appended.as_bytes())
+}
+
+fn dimension(roots: &[String]) -> djot_log::report::Dimension {
+    djot_log::report::Dimension {
+        roots: roots
+            .iter()
+            .flat_map(|r| djot_log::md::KindHeader::parse(r))
+            .map(|k| k.path)
+            .collect(),
+    }
+}
+
+fn print_pivot(pivot: &djot_log::report::Pivot) {
+    let label = |l: Option<&str>| l.unwrap_or("(none)").to_string();
+    let rows = pivot.rows();
+    let columns = pivot.columns();
+    let mut table = vec![std::iter::once(String::new())
+        .chain(columns.iter().map(|c| label(*c)))
+        .collect::<Vec<_>>()];
+    for row in rows.iter() {
+        table.push(
+            std::iter::once(label(*row))
+                .chain(columns.iter().map(|c| {
+                    let cell = pivot.get(*row, *c);
+                    if cell.is_zero() {
+                        String::new()
+                    } else {
+                        format_duration(cell)
+                    }
+                }))
+                .collect(),
+        );
+    }
+    let widths = (0..=columns.len())
+        .map(|i| {
+            table
+                .iter()
+                .map(|r| r[i].chars().count())
+                .max()
+                .unwrap_or(0)
         })
-        .unwrap_or(chrono::Local::now().date_naive());
+        .collect::<Vec<_>>();
+    for row in table {
+        let cells = row
+            .iter()
+            .zip(widths.iter())
+            .enumerate()
+            .map(|(i, (cell, width))| {
+                if i == 0 {
+                    format!("{:<width$}", cell)
+                } else {
+                    format!("{:>width$}", cell)
+                }
+            })
+            .collect::<Vec<_>>();
+        println!("{}", cells.join(" | ").trim_end());
+    }
+}
+
+fn pivot_json(pivot: &djot_log::report::Pivot) -> serde_json::Value {
+    serde_json::json!(pivot
+        .cells
+        .iter()
+        .map(|((row, column), total)| serde_json::json!({
+            "row": row,
+            "column": column,
+            "minutes": total.num_minutes(),
+        }))
+        .collect::<Vec<_>>())
+}
+
+fn format_duration(duration: chrono::TimeDelta) -> String {
+    let minutes = duration.num_minutes();
+    format!("{}h {}m", minutes / 60, minutes % 60)
+}
+
+fn percentage(duration: chrono::TimeDelta, total: chrono::TimeDelta) -> f64 {
+    if total.is_zero() {
+        0.0
+    } else {
+        100.0 * duration.num_seconds() as f64 / total.num_seconds() as f64
+    }
+}
+
+fn print_kind_tree(tree: &djot_log::report::KindTree, total: chrono::TimeDelta, depth: usize) {
+    if depth == 0 {
+        println!("Total: {}", format_duration(tree.total));
+    }
+    for (name, child) in tree.children.iter() {
+        println!(
+            "{}{} {} {:.1}%",
+            "  ".repeat(depth),
+            name,
+            format_duration(child.total),
+            percentage(child.total, total)
+        );
+        print_kind_tree(child, total, depth + 1);
+    }
+}
+
+fn kind_tree_json(
+    name: &str,
+    tree: &djot_log::report::KindTree,
+    total: chrono::TimeDelta,
+) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "minutes": tree.total.num_minutes(),
+        "percentage": percentage(tree.total, total),
+        "children": tree
+            .children
+            .iter()
+            .map(|(name, child)| kind_tree_json(name, child, total))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn print_text(
+    balance: &[(chrono::NaiveDate, chrono::TimeDelta, chrono::TimeDelta)],
+    calendar: &djot_log::calendar::Calendar,
+    show: djot_log::DateRange,
+    logs: &[(&djot_log::Log, &djot_log::Log)],
+    notes: bool,
+) {
+    println!("Balance:");
+    println!();
+
+    for (date, total, vs_target) in balance {
+        let day_off = match calendar.day_off(*date) {
+            Some((day_off, "")) => format!(" ({})", day_off),
+            Some((day_off, description)) => format!(" ({}: {})", day_off, description),
+            None => String::new(),
+        };
+        println!(
+            "day: {} {}, delta minutes {}{}",
+            date,
+            format_duration(*total),
+            vs_target.num_minutes(),
+            day_off
+        );
+    }
 
     println!();
     println!("Logs for {}:", show);
     println!();
 
-    for log in logs.iter().filter(|l| l.start.date() == show) {
-        println!("{}", log);
+    for (log, log_until_now) in logs {
+        // A running log that was not ended at `now` already says it is running.
+        if log.is_running() && !log_until_now.is_running() {
+            println!("{} (running)", log_until_now);
+        } else {
+            println!("{}", log_until_now);
+        }
+        if notes {
+            for note in log.notes() {
+                println!("    {}", note.to_string().replace('\n', "\n    "));
+            }
+        }
     }
+}
 
+fn print_json(
+    balance: &[(chrono::NaiveDate, chrono::TimeDelta, chrono::TimeDelta)],
+    calendar: &djot_log::calendar::Calendar,
+    logs: &[(&djot_log::Log, &djot_log::Log)],
+) -> Result<(), serde_json::Error> {
+    let balance = balance
+        .iter()
+        .map(|(date, total, vs_target)| {
+            serde_json::json!({
+                "date": date.to_string(),
+                "total_minutes": total.num_minutes(),
+                "delta_minutes": vs_target.num_minutes(),
+                "day_off": calendar.day_off(*date).map(|(day_off, _)| day_off.to_string()),
+                "day_off_description": calendar.day_off(*date).map(|(_, description)| description),
+            })
+        })
+        .collect::<Vec<_>>();
+    let logs = logs
+        .iter()
+        .map(|(log, log_until_now)| log_json(log, log_until_now))
+        .collect::<Vec<_>>();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "balance": balance,
+            "logs": logs,
+        }))?
+    );
     Ok(())
 }
+
+const JSON_DATE_TIME: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Takes the log both as parsed and as ended at the current time, to report running logs.
+fn log_json(log: &djot_log::Log, log_until_now: &djot_log::Log) -> serde_json::Value {
+    serde_json::json!({
+        "start": log.start.format(JSON_DATE_TIME).to_string(),
+        "end": log.end().map(|e| e.format(JSON_DATE_TIME).to_string()),
+        "running": log.is_running(),
+        "duration_minutes": log_until_now.duration().num_minutes(),
+        "kinds": log.kinds().paths(),
+        "notes": log.notes().iter().map(|n| n.to_string()).collect::<Vec<_>>(),
+    })
+}
@@ -1,12 +1,126 @@
 use chrono::naive;
+use itertools::Itertools;
 use markdown::mdast;
 
 #[derive(Clone, Debug)]
-#[allow(clippy::enum_variant_names)]
 pub enum LogNode {
     DayHeader(DayHeader),
     TimeHeader(TimeHeader),
     KindHeader(KindHeader),
+    Note(Note),
+}
+
+/// Content of an entry that is not a header, such as the bullets describing the work done.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Note {
+    Paragraph(Vec<Inline>),
+    /// Each item holds the notes nested in it.
+    List(Vec<Vec<Note>>),
+}
+
+/// Text within a note; formatting such as emphasis is dropped.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Inline {
+    Text(String),
+    Link { text: String, url: String },
+}
+
+impl Inline {
+    /// Appends text to `inlines`, merging it with a preceding text.
+    pub fn push_text(inlines: &mut Vec<Inline>, text: &str) {
+        match inlines.last_mut() {
+            Some(Inline::Text(last)) => last.push_str(text),
+            _ => inlines.push(Inline::Text(text.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Inline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Inline::Text(text) => write!(f, "{}", text),
+            Inline::Link { text, url } => write!(f, "[{}]({})", text, url),
+        }
+    }
+}
+
+impl std::fmt::Display for Note {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Note::Paragraph(inlines) => write!(f, "{}", inlines.iter().join("")),
+            Note::List(items) => write!(
+                f,
+                "{}",
+                items
+                    .iter()
+                    .map(|item| format!("* {}", item.iter().join("\n").replace('\n', "\n  ")))
+                    .join("\n")
+            ),
+        }
+    }
+}
+
+/// A line and column in the source of a log, both starting at 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Computes the position of a byte offset into `s`.
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     djot_log::md::Position::from_offset("# 2023-12-03\n\n## 09:00\n", 17),
+    ///     djot_log::md::Position { line: 3, column: 4 }
+    /// );
+    /// ```
+    pub fn from_offset(s: &str, offset: usize) -> Position {
+        let before = &s[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Position {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl From<&markdown::unist::Position> for Position {
+    fn from(position: &markdown::unist::Position) -> Position {
+        Position {
+            line: position.start.line,
+            column: position.start.column,
+        }
+    }
+}
+
+/// A value together with the position where it starts in the source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Located<T> {
+    pub value: T,
+    pub position: Position,
+}
+
+impl LogNode {
+    /// Interprets the text of a heading of the given depth, regardless of the input format.
+    pub fn from_heading(depth: u8, text: &str) -> Option<LogNode> {
+        match depth {
+            1 => DayHeader::parse(text).map(LogNode::DayHeader),
+            2 => TimeHeader::parse(text).map(LogNode::TimeHeader),
+            3 => KindHeader::parse(text).map(LogNode::KindHeader),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -14,21 +128,46 @@ pub struct DayHeader {
     pub date: naive::NaiveDate,
 }
 
+impl DayHeader {
+    pub fn parse(text: &str) -> Option<DayHeader> {
+        Some(DayHeader {
+            date: naive::NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?,
+        })
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TimeHeader {
     pub time: naive::NaiveTime,
 }
 
+impl TimeHeader {
+    pub fn parse(text: &str) -> Option<TimeHeader> {
+        Some(TimeHeader {
+            time: naive::NaiveTime::parse_from_str(text, "%H:%M").ok()?,
+        })
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct KindHeader {
     pub path: Vec<String>,
 }
 
+impl KindHeader {
+    pub fn parse(text: &str) -> Option<KindHeader> {
+        Some(KindHeader {
+            path: text.split(" / ").map(|x| x.to_string()).collect(),
+        })
+    }
+}
+
 pub trait NodeExt {
     fn expect_root(self) -> mdast::Root;
     fn to_day_header(&self) -> Option<DayHeader>;
     fn to_time_header(&self) -> Option<TimeHeader>;
     fn to_kind_header(&self) -> Option<KindHeader>;
+    fn to_note(&self) -> Option<Note>;
     fn to_log_node(&self) -> Option<LogNode>;
     fn get_first_text_value_of_header_of_depth(&self, header_depth: u8) -> Option<String>;
 }
@@ -44,13 +183,7 @@ impl NodeExt for mdast::Node {
     /// );
     /// ```
     fn to_day_header(&self) -> Option<DayHeader> {
-        Some(DayHeader {
-            date: naive::NaiveDate::parse_from_str(
-                &(self.get_first_text_value_of_header_of_depth(1)?),
-                "%Y-%m-%d",
-            )
-            .ok()?,
-        })
+        DayHeader::parse(&self.get_first_text_value_of_header_of_depth(1)?)
     }
 
     /// ```
@@ -63,13 +196,7 @@ impl NodeExt for mdast::Node {
     /// );
     /// ```
     fn to_time_header(&self) -> Option<TimeHeader> {
-        Some(TimeHeader {
-            time: naive::NaiveTime::parse_from_str(
-                &(self.get_first_text_value_of_header_of_depth(2)?),
-                "%H:%M",
-            )
-            .ok()?,
-        })
+        TimeHeader::parse(&self.get_first_text_value_of_header_of_depth(2)?)
     }
 
     /// ```
@@ -82,12 +209,7 @@ impl NodeExt for mdast::Node {
     /// );
     /// ```
     fn to_kind_header(&self) -> Option<KindHeader> {
-        Some(KindHeader {
-            path: (self.get_first_text_value_of_header_of_depth(3))?
-                .split(" / ")
-                .map(|x| x.to_string())
-                .collect(),
-        })
+        KindHeader::parse(&self.get_first_text_value_of_header_of_depth(3)?)
     }
 
     fn get_first_text_value_of_header_of_depth(&self, header_depth: u8) -> Option<String> {
@@ -107,11 +229,47 @@ impl NodeExt for mdast::Node {
         }
     }
 
+    /// ```
+    /// use djot_log::md::{Inline, Note, NodeExt};
+    /// assert_eq!(
+    ///     djot_log::md::parse_markdown("* Fixed *the* [bug](https://example.com/1)\n").children[0]
+    ///         .to_note(),
+    ///     Some(Note::List(vec![vec![Note::Paragraph(vec![
+    ///         Inline::Text("Fixed the ".into()),
+    ///         Inline::Link {
+    ///             text: "bug".into(),
+    ///             url: "https://example.com/1".into()
+    ///         },
+    ///     ])]]))
+    /// );
+    /// ```
+    fn to_note(&self) -> Option<Note> {
+        match self {
+            mdast::Node::Paragraph(mdast::Paragraph { children, .. }) => {
+                let mut inlines = vec![];
+                push_inlines(children, &mut inlines);
+                Some(Note::Paragraph(inlines))
+            }
+            mdast::Node::List(mdast::List { children, .. }) => Some(Note::List(
+                children
+                    .iter()
+                    .map(|item| {
+                        item.children()
+                            .map(|c| c.iter().flat_map(NodeExt::to_note).collect())
+                            .unwrap_or_default()
+                    })
+                    .collect(),
+            )),
+            _ => None,
+        }
+    }
+
     fn to_log_node(&self) -> Option<LogNode> {
         [
             self.to_day_header().map(LogNode::DayHeader),
             self.to_time_header().map(LogNode::TimeHeader),
             self.to_kind_header().map(LogNode::KindHeader),
+            self.to_note().map(LogNode::Note),
         ]
         .iter()
         .flatten()
@@ -129,6 +287,35 @@ impl NodeExt for mdast::Node {
     }
 }
 
+fn push_inlines(nodes: &[mdast::Node], inlines: &mut Vec<Inline>) {
+    for node in nodes {
+        match node {
+            mdast::Node::Text(mdast::Text { value, .. })
+            | mdast::Node::InlineCode(mdast::InlineCode { value, .. }) => {
+                Inline::push_text(inlines, value)
+            }
+            mdast::Node::Break(_) => Inline::push_text(inlines, "\n"),
+            mdast::Node::Link(mdast::Link { url, .. }) => inlines.push(Inline::Link {
+                text: node.to_string(),
+                url: url.clone(),
+            }),
+            _ => push_inlines(
+                node.children().map(Vec::as_slice).unwrap_or_default(),
+                inlines,
+            ),
+        }
+    }
+}
+
+/// The CommonMark log format.
+pub struct Markdown;
+
+impl crate::LogSource for Markdown {
+    fn log_nodes(&self, s: &str) -> Vec<Located<LogNode>> {
+        parse_log_nodes(&parse_markdown(s)).collect()
+    }
+}
+
 pub fn parse_markdown(s: &str) -> mdast::Root {
     markdown::to_mdast(s, &markdown::ParseOptions::default())
         .unwrap()
@@ -139,7 +326,9 @@ pub fn parse_markdown(s: &str) -> mdast::Root {
 /// let source = std::fs::read_to_string("example.md").unwrap();
 /// let debug = format!(
 ///     "{:?}",
-///     djot_log::md::parse_log_nodes(&djot_log::md::parse_markdown(&source)).collect::<Vec<_>>()
+///     djot_log::md::parse_log_nodes(&djot_log::md::parse_markdown(&source))
+///         .map(|n| n.value)
+///         .collect::<Vec<_>>()
 /// )
 /// .strip_prefix("[")
 /// .unwrap()
@@ -152,25 +341,175 @@ pub fn parse_markdown(s: &str) -> mdast::Root {
 /// TimeHeader(TimeHeader { time: 09:00:00 })
 /// KindHeader(KindHeader { path: ["Work", "MyOrg", "MyDept", "MyProj"] })
 /// KindHeader(KindHeader { path: ["Coding"] })
+/// Note(List([[Paragraph([Text("X")])]]))
 /// TimeHeader(TimeHeader { time: 13:00:00 })
 /// TimeHeader(TimeHeader { time: 14:00:00 })
 /// KindHeader(KindHeader { path: ["Work", "MyOrg", "MyDept"] })
 /// KindHeader(KindHeader { path: ["Meeting"] })
+/// Note(List([[Paragraph([Text("Interesting!")])]]))
 /// TimeHeader(TimeHeader { time: 15:00:00 })
 /// KindHeader(KindHeader { path: ["Work", "MyOrg", "MyDept", "MyProj"] })
 /// KindHeader(KindHeader { path: ["Coding"] })
+/// Note(List([[Paragraph([Text("X")])]]))
 /// TimeHeader(TimeHeader { time: 18:00:00 })
 /// DayHeader(DayHeader { date: 2023-12-04 })
 /// TimeHeader(TimeHeader { time: 09:00:00 })
 /// KindHeader(KindHeader { path: ["Work", "MyOrg", "MyDept", "MyProj"] })
 /// KindHeader(KindHeader { path: ["Coding"] })
+/// Note(List([[Paragraph([Text("X")])]]))
 /// TimeHeader(TimeHeader { time: 13:00:00 })
 /// TimeHeader(TimeHeader { time: 14:00:00 })
 /// KindHeader(KindHeader { path: ["Work", "MyOrg", "MyDept", "MyProj"] })
 /// KindHeader(KindHeader { path: ["Coding"] })
+/// Note(List([[Paragraph([Text("X")])]]))
 /// TimeHeader(TimeHeader { time: 18:00:00 })"##
 /// )
 /// ```
-pub fn parse_log_nodes(md: &mdast::Root) -> impl Iterator<Item = LogNode> + '_ {
-    md.children.iter().flat_map(NodeExt::to_log_node)
+pub fn parse_log_nodes(md: &mdast::Root) -> impl Iterator<Item = Located<LogNode>> + '_ {
+    md.children.iter().flat_map(|n| {
+        Some(Located {
+            value: n.to_log_node()?,
+            position: n.position()?.into(),
+        })
+    })
+}
+
+/// Writes logs as Markdown, with a day header for each day an entry starts on, and time headers
+/// shared between entries that follow each other.
+///
+/// Parsing the written logs with [`crate::parse_log`] gives the same logs, as long as they are
+/// sorted and do not overlap, start and end on a whole minute, last less than a day, have at least
+/// one kind, and only the last is running. The parts of kind paths and the notes must also be as
+/// the parser returns them, such as without surrounding whitespace.
+///
+/// ```
+/// use djot_log::md::{write_log, Inline, Note};
+/// let source = std::fs::read_to_string("example.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// assert_eq!(djot_log::parse_log(&write_log(&logs)), (logs, vec![]));
+///
+/// let at = |s| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+/// let kinds = djot_log::Kinds::new([vec!["Work".to_string(), "C#".to_string()]].into());
+/// let notes = vec![Note::List(vec![vec![Note::Paragraph(vec![
+///     Inline::Text("Fixed *all* ".to_string()),
+///     Inline::Link {
+///         text: "bugs".to_string(),
+///         url: "https://example.com/bugs?q=a b".to_string(),
+///     },
+/// ])]])];
+/// let logs = vec![
+///     djot_log::Log::new(at("2023-12-03 22:00"), Some(at("2023-12-04 01:30")), kinds.clone(), notes),
+///     djot_log::Log::new(at("2023-12-04 09:00"), None, kinds, vec![]),
+/// ];
+/// let written = write_log(&logs);
+/// assert_eq!(
+///     written,
+///     "\
+/// ## 2023-12-03
+///
+/// ### 22:00
+///
+/// #### Work / C\\#
+///
+/// * Fixed \\*all\\* [bugs](<https://example.com/bugs?q=a b>)
+///
+/// ### 01:30
+///
+/// ## 2023-12-04
+///
+/// ### 09:00
+///
+/// #### Work / C\\#
+/// "
+/// );
+/// assert_eq!(djot_log::parse_log(&written), (logs, vec![]));
+/// ```
+pub fn write_log(logs: &[crate::Log]) -> String {
+    let mut blocks = vec![];
+    let mut day = None;
+    let mut open: Option<naive::NaiveDateTime> = None;
+    for log in logs {
+        if day != Some(log.start.date()) {
+            if let Some(end) = open.take() {
+                blocks.push(format!("## {}", end.format("%H:%M")));
+            }
+            blocks.push(format!("# {}", log.start.date()));
+            day = Some(log.start.date());
+        }
+        if let Some(end) = open.take().filter(|end| *end != log.start) {
+            blocks.push(format!("## {}", end.format("%H:%M")));
+        }
+        blocks.push(format!("## {}", log.start.format("%H:%M")));
+        for path in log.kinds.paths() {
+            blocks.push(format!(
+                "### {}",
+                path.iter().map(|p| escape(p)).join(" / ")
+            ));
+        }
+        for (i, note) in log.notes.iter().enumerate() {
+            // Consecutive lists with the same marker would be parsed as one.
+            let marker = if i % 2 == 0 { '*' } else { '-' };
+            blocks.push(write_note(note, marker));
+        }
+        open = log.end;
+    }
+    if let Some(end) = open {
+        blocks.push(format!("## {}", end.format("%H:%M")));
+    }
+    blocks.iter().map(|b| format!("{}\n", b)).join("\n")
+}
+
+fn write_note(note: &Note, marker: char) -> String {
+    match note {
+        Note::Paragraph(inlines) => inlines
+            .iter()
+            .map(|inline| match inline {
+                Inline::Text(text) => escape(text),
+                Inline::Link { text, url } => format!(
+                    "[{}](<{}>)",
+                    escape(text),
+                    url.replace('\\', "\\\\")
+                        .replace('<', "\\<")
+                        .replace('>', "\\>")
+                ),
+            })
+            .join(""),
+        Note::List(items) => items
+            .iter()
+            .map(|item| {
+                let notes = item
+                    .iter()
+                    .enumerate()
+                    .map(|(i, note)| write_note(note, if i % 2 == 0 { '*' } else { '-' }))
+                    .join("\n\n")
+                    .replace('\n', "\n  ")
+                    .replace("\n  \n", "\n\n");
+                format!("{} {}", marker, notes).trim_end().to_string()
+            })
+            .join("\n"),
+    }
+}
+
+/// Escapes the characters of `text` that Markdown could read as formatting.
+fn escape(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            let mut escaped = String::new();
+            for (i, c) in line.char_indices() {
+                let starts_block = (i == 0 && c.is_ascii_punctuation())
+                    || (i == digits && i > 0 && matches!(c, '.' | ')'));
+                if starts_block
+                    || matches!(
+                        c,
+                        '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '&' | '#'
+                    )
+                {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        })
+        .join("\n")
 }
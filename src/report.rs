@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::{Kinds, Log};
+
+/// Time spent on a kind path and on each of its subpaths.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct KindTree {
+    pub total: chrono::TimeDelta,
+    pub children: BTreeMap<String, KindTree>,
+}
+
+/// Adds up the time of the logs at each level of their kind paths.
+///
+/// The total of the root is the time of all the logs. A log counts once towards each path
+/// prefix in its kinds, so the totals of the children of a node can add up to more than the
+/// total of the node when logs have several kinds.
+///
+/// ```
+/// let source = std::fs::read_to_string("example.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// let tree = djot_log::report::kind_tree(logs.iter());
+/// assert_eq!(tree.total.num_hours(), 16);
+/// assert_eq!(tree.children["Coding"].total.num_hours(), 15);
+/// let my_dept = &tree.children["Work"].children["MyOrg"].children["MyDept"];
+/// assert_eq!(my_dept.total.num_hours(), 16);
+/// assert_eq!(my_dept.children["MyProj"].total.num_hours(), 15);
+/// ```
+pub fn kind_tree<'a>(logs: impl Iterator<Item = &'a Log>) -> KindTree {
+    let mut root = KindTree::default();
+    for log in logs {
+        let duration = log.duration();
+        root.total += duration;
+        let prefixes = log
+            .kinds()
+            .paths()
+            .into_iter()
+            .flat_map(|p| (1..=p.len()).map(move |i| &p[..i]))
+            .collect::<HashSet<_>>();
+        for prefix in prefixes {
+            let mut node = &mut root;
+            for segment in prefix {
+                node = node.children.entry(segment.clone()).or_default();
+            }
+            node.total += duration;
+        }
+    }
+    root
+}
+
+/// Kind paths that are alternatives to each other, such as projects or activities, given by the
+/// roots they start with.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dimension {
+    pub roots: Vec<Vec<String>>,
+}
+
+impl Dimension {
+    /// The kind paths of the log that start with one of the roots of the dimension.
+    pub fn paths<'a>(&self, kinds: &'a Kinds) -> Vec<&'a [String]> {
+        kinds
+            .paths()
+            .into_iter()
+            .filter(|p| self.roots.iter().any(|r| p.starts_with(r)))
+            .collect()
+    }
+}
+
+/// Time spent on each combination of the paths of two dimensions.
+///
+/// `None` stands for the logs with no path in a dimension.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Pivot {
+    pub cells: BTreeMap<(Option<String>, Option<String>), chrono::TimeDelta>,
+}
+
+impl Pivot {
+    pub fn rows(&self) -> BTreeSet<Option<&str>> {
+        self.cells.keys().map(|(r, _)| r.as_deref()).collect()
+    }
+
+    pub fn columns(&self) -> BTreeSet<Option<&str>> {
+        self.cells.keys().map(|(_, c)| c.as_deref()).collect()
+    }
+
+    pub fn get(&self, row: Option<&str>, column: Option<&str>) -> chrono::TimeDelta {
+        self.cells
+            .get(&(row.map(str::to_string), column.map(str::to_string)))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Cross-tabulates the time of the logs between the paths of two dimensions.
+///
+/// A log with several paths in a dimension has its time split evenly between them.
+///
+/// ```
+/// use djot_log::report::Dimension;
+/// let source = std::fs::read_to_string("example.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// let projects = Dimension {
+///     roots: vec![vec!["Work".to_string()]],
+/// };
+/// let activities = Dimension {
+///     roots: vec![vec!["Coding".to_string()], vec!["Meeting".to_string()]],
+/// };
+/// let pivot = djot_log::report::pivot(logs.iter(), &projects, &activities);
+/// assert_eq!(
+///     pivot
+///         .get(Some("Work / MyOrg / MyDept / MyProj"), Some("Coding"))
+///         .num_hours(),
+///     15
+/// );
+/// assert_eq!(
+///     pivot.get(Some("Work / MyOrg / MyDept"), Some("Meeting")).num_hours(),
+///     1
+/// );
+/// assert_eq!(
+///     pivot.get(Some("Work / MyOrg / MyDept"), Some("Coding")).num_hours(),
+///     0
+/// );
+/// ```
+pub fn pivot<'a>(
+    logs: impl Iterator<Item = &'a Log>,
+    rows: &Dimension,
+    columns: &Dimension,
+) -> Pivot {
+    let mut pivot = Pivot::default();
+    for log in logs {
+        let labels = |dimension: &Dimension| {
+            let paths = dimension.paths(log.kinds());
+            if paths.is_empty() {
+                vec![None]
+            } else {
+                paths.iter().map(|p| Some(p.join(" / "))).collect()
+            }
+        };
+        let (row_labels, column_labels) = (labels(rows), labels(columns));
+        let share = log.duration() / (row_labels.len() * column_labels.len()) as i32;
+        for row in row_labels.iter() {
+            for column in column_labels.iter() {
+                *pivot
+                    .cells
+                    .entry((row.clone(), column.clone()))
+                    .or_default() += share;
+            }
+        }
+    }
+    pivot
+}
@@ -0,0 +1,263 @@
+use chrono::{naive, Datelike};
+
+/// The time to work on a given day.
+pub trait DailyTarget {
+    fn target(&self, date: naive::NaiveDate) -> chrono::TimeDelta;
+}
+
+impl<T: DailyTarget + ?Sized> DailyTarget for &T {
+    fn target(&self, date: naive::NaiveDate) -> chrono::TimeDelta {
+        (**self).target(date)
+    }
+}
+
+/// The time to work on each day of the week.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Schedule {
+    /// Indexed by the number of days from Monday.
+    targets: [chrono::TimeDelta; 7],
+}
+
+impl Schedule {
+    /// The same target for every day of the week.
+    pub fn flat(target: chrono::TimeDelta) -> Schedule {
+        Schedule {
+            targets: [target; 7],
+        }
+    }
+}
+
+impl DailyTarget for Schedule {
+    fn target(&self, date: naive::NaiveDate) -> chrono::TimeDelta {
+        self.targets[date.weekday().num_days_from_monday() as usize]
+    }
+}
+
+/// Parses a possibly fractional and signed number of hours, such as `6.5` or `-2`.
+pub fn parse_hours(s: &str) -> Result<chrono::TimeDelta, String> {
+    let hours = s
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("expected a number of hours: {}", s))?;
+    chrono::TimeDelta::try_minutes((hours * 60.0).round() as i64)
+        .ok_or_else(|| format!("too many hours: {}", s))
+}
+
+/// Parses comma-separated days or ranges of days with their hours, such as
+/// `mon-thu=8,fri=6.5`. Days that are not mentioned have no target.
+///
+/// ```
+/// use djot_log::schedule::{DailyTarget, Schedule};
+/// let schedule: Schedule = "mon-thu=8,fri=6.5".parse().unwrap();
+/// let minutes = |d| {
+///     schedule
+///         .target(chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap())
+///         .num_minutes()
+/// };
+/// assert_eq!(minutes("2023-12-04"), 480);
+/// assert_eq!(minutes("2023-12-08"), 390);
+/// assert_eq!(minutes("2023-12-09"), 0);
+/// assert!("mon=8,funday=2".parse::<Schedule>().is_err());
+/// ```
+impl std::str::FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Schedule, String> {
+        let mut targets = [chrono::TimeDelta::zero(); 7];
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (days, hours) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected day=hours: {}", part))?;
+            let target = parse_hours(hours)?;
+            let (first, last) = days.split_once('-').unwrap_or((days, days));
+            let parse_day = |d: &str| {
+                d.trim()
+                    .parse::<chrono::Weekday>()
+                    .map(|d| d.num_days_from_monday() as usize)
+                    .map_err(|_| format!("expected a day of the week: {}", d))
+            };
+            let (first, last) = (parse_day(first)?, parse_day(last)?);
+            if first > last {
+                return Err(format!("days out of order: {}", days));
+            }
+            targets[first..=last].fill(target);
+        }
+        Ok(Schedule { targets })
+    }
+}
+
+/// Schedules that take effect from a date on, such as after a change of contract.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DatedSchedule {
+    /// The schedule under `None` applies before the first dated one.
+    changes: std::collections::BTreeMap<Option<naive::NaiveDate>, Schedule>,
+}
+
+impl DatedSchedule {
+    /// The schedule in effect on `date`, if any.
+    pub fn schedule_on(&self, date: naive::NaiveDate) -> Option<&Schedule> {
+        self.changes
+            .range(..=Some(date))
+            .next_back()
+            .map(|(_, schedule)| schedule)
+    }
+}
+
+impl From<Schedule> for DatedSchedule {
+    fn from(schedule: Schedule) -> DatedSchedule {
+        DatedSchedule {
+            changes: [(None, schedule)].into(),
+        }
+    }
+}
+
+/// Days before the first schedule have no target.
+impl DailyTarget for DatedSchedule {
+    fn target(&self, date: naive::NaiveDate) -> chrono::TimeDelta {
+        self.schedule_on(date)
+            .map_or_else(chrono::TimeDelta::zero, |s| s.target(date))
+    }
+}
+
+/// Parses one schedule per line, optionally preceded by the day it takes effect. A schedule
+/// without a day applies from the beginning. Empty lines and lines starting with `#` are ignored.
+///
+/// ```
+/// use djot_log::schedule::{DailyTarget, DatedSchedule};
+/// let schedule: DatedSchedule = "
+/// ## Full time, then four days a week
+/// mon-fri=8
+/// 2024-03-01 mon-thu=8
+/// "
+/// .parse()
+/// .unwrap();
+/// let minutes = |d| {
+///     schedule
+///         .target(chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap())
+///         .num_minutes()
+/// };
+/// assert_eq!(minutes("2024-02-23"), 480);
+/// assert_eq!(minutes("2024-03-01"), 0);
+/// assert_eq!(minutes("2024-03-04"), 480);
+/// ```
+impl std::str::FromStr for DatedSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DatedSchedule, String> {
+        let mut changes = std::collections::BTreeMap::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (from, schedule) = match line.split_once(char::is_whitespace) {
+                Some((from, schedule)) if !from.contains('=') => (
+                    Some(
+                        naive::NaiveDate::parse_from_str(from, "%Y-%m-%d")
+                            .map_err(|_| format!("line {}: expected a day: {}", i + 1, from))?,
+                    ),
+                    schedule,
+                ),
+                _ => (None, line),
+            };
+            let schedule = schedule
+                .parse()
+                .map_err(|e| format!("line {}: {}", i + 1, e))?;
+            if changes.insert(from, schedule).is_some() {
+                return Err(format!(
+                    "line {}: schedule given twice for the same day",
+                    i + 1
+                ));
+            }
+        }
+        Ok(DatedSchedule { changes })
+    }
+}
+
+/// The balance carried over before the first day, and the days on which the balance was settled
+/// with the balance it restarts from, as taken by [`crate::with_balance_resets`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Balance {
+    pub opening: chrono::TimeDelta,
+    pub settled: std::collections::BTreeMap<naive::NaiveDate, chrono::TimeDelta>,
+}
+
+/// Parses a day on which the balance was settled, optionally with the balance in hours it
+/// restarts from, such as `2024-01-01` or `2024-01-01=2.5`. Without hours it restarts from zero.
+pub fn parse_settlement(s: &str) -> Result<(naive::NaiveDate, chrono::TimeDelta), String> {
+    let (date, hours) = s.split_once('=').unwrap_or((s, "0"));
+    Ok((
+        date.trim()
+            .parse()
+            .map_err(|_| format!("expected a day such as 2024-01-01: {}", date))?,
+        parse_hours(hours)?,
+    ))
+}
+
+/// Parses an `opening` line with the opening balance in hours, and `settled` lines with a day as
+/// taken by [`parse_settlement`]. Empty lines and lines starting with `#` are ignored.
+///
+/// ```
+/// use djot_log::schedule::Balance;
+/// let balance: Balance = "
+/// ## Carried over from the old spreadsheet
+/// opening 12
+/// settled 2024-01-01
+/// settled 2024-07-01=-2.5
+/// "
+/// .parse()
+/// .unwrap();
+/// assert_eq!(balance.opening.num_minutes(), 720);
+/// assert_eq!(
+///     balance
+///         .settled
+///         .iter()
+///         .map(|(date, hours)| (date.to_string(), hours.num_minutes()))
+///         .collect::<Vec<_>>(),
+///     vec![("2024-01-01".to_string(), 0), ("2024-07-01".to_string(), -150)]
+/// );
+/// assert_eq!(
+///     "opening 1\nopening 2".parse::<Balance>(),
+///     Err("line 2: opening balance given twice".to_string())
+/// );
+/// ```
+impl std::str::FromStr for Balance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Balance, String> {
+        let mut opening = None;
+        let mut settled = std::collections::BTreeMap::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once(char::is_whitespace) {
+                Some(("opening", hours)) => {
+                    let hours = parse_hours(hours).map_err(|e| format!("line {}: {}", i + 1, e))?;
+                    if opening.replace(hours).is_some() {
+                        return Err(format!("line {}: opening balance given twice", i + 1));
+                    }
+                }
+                Some(("settled", settlement)) => {
+                    let (date, hours) = parse_settlement(settlement.trim())
+                        .map_err(|e| format!("line {}: {}", i + 1, e))?;
+                    if settled.insert(date, hours).is_some() {
+                        return Err(format!("line {}: settled twice on the same day", i + 1));
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "line {}: expected opening or settled: {}",
+                        i + 1,
+                        line
+                    ))
+                }
+            }
+        }
+        Ok(Balance {
+            opening: opening.unwrap_or_default(),
+            settled,
+        })
+    }
+}
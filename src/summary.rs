@@ -0,0 +1,85 @@
+use chrono::naive;
+use itertools::Itertools;
+
+use crate::schedule::DailyTarget;
+
+/// A span of days to add up daily totals over.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Period {
+    /// An ISO week, starting on Monday.
+    Week,
+    Month,
+    Year,
+}
+
+impl Period {
+    /// Names the period that contains `date`, such as `2023-W49`, `2023-12` or `2023`.
+    pub fn label(self, date: naive::NaiveDate) -> String {
+        match self {
+            Period::Week => date.format("%G-W%V").to_string(),
+            Period::Month => date.format("%Y-%m").to_string(),
+            Period::Year => date.format("%Y").to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Summary {
+    pub period: String,
+    pub total: chrono::TimeDelta,
+    pub target: chrono::TimeDelta,
+    /// Days with any time logged.
+    pub days_worked: usize,
+}
+
+impl Summary {
+    pub fn difference(&self) -> chrono::TimeDelta {
+        self.total - self.target
+    }
+}
+
+/// Adds up the totals of each day, as returned by [`crate::total_by_day`], over each period.
+///
+/// The target of a period adds up the targets of the days in `totals`, so include the days with
+/// no logs using [`crate::add_missing_days`] to count them against the target.
+///
+/// ```
+/// use djot_log::summary::{summarize, Period};
+/// let source = std::fs::read_to_string("example_unbalanced.md").unwrap();
+/// let (logs, _) = djot_log::parse_log(&source);
+/// let totals = djot_log::total_by_day(logs.iter(), djot_log::DayAttribution::StartDay);
+/// let schedule = djot_log::schedule::Schedule::flat(chrono::TimeDelta::try_hours(8).unwrap());
+/// let weeks = summarize(&totals, Period::Week, &schedule);
+/// assert_eq!(
+///     weeks
+///         .iter()
+///         .map(|s| (
+///             s.period.as_str(),
+///             s.total.num_minutes(),
+///             s.difference().num_minutes(),
+///             s.days_worked
+///         ))
+///         .collect::<Vec<_>>(),
+///     vec![("2023-W48", 480, 0, 1), ("2023-W49", 1440, 0, 3)]
+/// );
+/// ```
+pub fn summarize(
+    totals: &[(naive::NaiveDate, chrono::TimeDelta)],
+    period: Period,
+    target: &impl DailyTarget,
+) -> Vec<Summary> {
+    totals
+        .iter()
+        .group_by(|(date, _)| period.label(*date))
+        .into_iter()
+        .map(|(label, days)| {
+            let days = days.collect::<Vec<_>>();
+            Summary {
+                period: label,
+                total: days.iter().map(|(_, total)| *total).sum(),
+                target: days.iter().map(|(date, _)| target.target(*date)).sum(),
+                days_worked: days.iter().filter(|(_, total)| !total.is_zero()).count(),
+            }
+        })
+        .collect()
+}